@@ -18,6 +18,25 @@ fn main() {
     }
 }
 
+// Helper modules used by the Rhai modules, mirroring the crate layout.
+#[cfg(all(feature = "metadata", feature = "float"))]
+#[path = "src/convert.rs"]
+mod convert;
+
+#[cfg(feature = "metadata")]
+#[path = "src/limits.rs"]
+mod limits;
+
+// Package setup and seeding are not needed to generate documentation.
+#[cfg(feature = "metadata")]
+#[allow(dead_code)]
+#[path = "src/rng.rs"]
+mod rng;
+
+#[cfg(all(feature = "metadata", feature = "float", feature = "array"))]
+#[path = "src/weights.rs"]
+mod weights;
+
 #[cfg(feature = "metadata")]
 mod doc_gen {
     use rhai::{plugin::*, Engine};
@@ -27,8 +46,32 @@ mod doc_gen {
 
     // Rhai modules in the `rhai-rand` package.
     mod pkg {
-        include!("src/rand.rs");
-        include!("src/array.rs");
+        pub mod rand {
+            include!("src/rand.rs");
+        }
+        #[cfg(feature = "array")]
+        pub mod array {
+            include!("src/array.rs");
+        }
+        #[cfg(feature = "array")]
+        pub mod blob {
+            include!("src/blob.rs");
+        }
+        pub mod map {
+            include!("src/map.rs");
+        }
+        #[cfg(feature = "float")]
+        pub mod color {
+            include!("src/color.rs");
+        }
+        #[cfg(feature = "pattern")]
+        pub mod pattern {
+            include!("src/pattern.rs");
+        }
+        #[cfg(feature = "uuid")]
+        pub mod uuid {
+            include!("src/uuid.rs");
+        }
     }
 
     #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -104,8 +147,30 @@ mod doc_gen {
     pub fn generate_doc(writer: &mut impl Write) {
         let mut engine = Engine::new();
         let mut fs_module = Module::new();
-        combine_with_exported_module!(&mut fs_module, "rhai_lib_path", pkg::rand_functions);
-        combine_with_exported_module!(&mut fs_module, "rhai_file_path", pkg::array_functions);
+        combine_with_exported_module!(&mut fs_module, "rhai_lib_path", pkg::rand::rand_functions);
+        #[cfg(feature = "array")]
+        combine_with_exported_module!(
+            &mut fs_module,
+            "rhai_file_path",
+            pkg::array::array_functions
+        );
+        #[cfg(feature = "array")]
+        combine_with_exported_module!(&mut fs_module, "rhai_blob_path", pkg::blob::blob_functions);
+        combine_with_exported_module!(&mut fs_module, "rhai_map_path", pkg::map::map_functions);
+        #[cfg(feature = "float")]
+        combine_with_exported_module!(
+            &mut fs_module,
+            "rhai_color_path",
+            pkg::color::color_functions
+        );
+        #[cfg(feature = "pattern")]
+        combine_with_exported_module!(
+            &mut fs_module,
+            "rhai_pattern_path",
+            pkg::pattern::pattern_functions
+        );
+        #[cfg(feature = "uuid")]
+        combine_with_exported_module!(&mut fs_module, "rhai_uuid_path", pkg::uuid::uuid_functions);
         engine.register_global_module(fs_module.into());
 
         // Extract metadata
@@ -125,12 +190,11 @@ mod doc_gen {
                 let (prefix, name, signature) = fmt_fn_name(name, signature);
 
                 // Check if there are multiple arities, and if so add a header and indent
-                if idx < function_list.len() - 1 {
-                    if name == function_list[idx + 1].name && !indented {
-                        writeln!(writer, "## {prefix}`{}`", name.to_owned())
-                            .expect("Cannot write to {doc_file}");
-                        indented = true;
-                    }
+                if idx < function_list.len() - 1 && name == function_list[idx + 1].name && !indented
+                {
+                    writeln!(writer, "## {prefix}`{}`", name.to_owned())
+                        .expect("Cannot write to {doc_file}");
+                    indented = true;
                 }
 
                 // Print definition with right level of indentation
@@ -143,10 +207,12 @@ mod doc_gen {
                 }
 
                 // End indentation when its time
-                if idx != 0 && idx < function_list.len() - 1 {
-                    if name == function_list[idx - 1].name && name != function_list[idx + 1].name {
-                        indented = false;
-                    }
+                if idx != 0
+                    && idx < function_list.len() - 1
+                    && name == function_list[idx - 1].name
+                    && name != function_list[idx + 1].name
+                {
+                    indented = false;
                 }
             }
         }
@@ -1,10 +1,76 @@
 #[allow(unused_imports)]
 use rhai::plugin::*;
 
+#[cfg(feature = "float")]
+use crate::convert::to_float;
+#[cfg(feature = "float")]
+use rhai::FLOAT;
+
+/// Check if two values are equal, using the `==` operator.
+///
+/// Values of different types without an `==` operator between them are not equal.
+fn equals(
+    ctx: &NativeCallContext,
+    a: &mut Dynamic,
+    b: &Dynamic,
+) -> Result<bool, Box<EvalAltResult>> {
+    let equals = ctx
+        .call_native_fn_raw("==", true, &mut [a, &mut b.clone()])
+        .or_else(|err| match *err {
+            EvalAltResult::ErrorFunctionNotFound(..) if a.type_id() != b.type_id() => {
+                Ok(Dynamic::FALSE)
+            }
+            _ => Err(err),
+        })?;
+
+    Ok(equals.as_bool().unwrap_or(false))
+}
+
+/// Check if an array contains a value, using the `==` operator.
+fn contains(
+    ctx: &NativeCallContext,
+    array: &mut rhai::Array,
+    value: &Dynamic,
+) -> Result<bool, Box<EvalAltResult>> {
+    for item in array {
+        if equals(ctx, item, value)? {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+/// Hash a string into a uniformly-distributed number between `0.0` and `1.0` (exclusive).
+///
+/// This uses FNV-1a followed by the SplitMix64 finalizer, so the result is stable across
+/// platforms, runs and versions.
+#[cfg(feature = "float")]
+fn hash_to_unit(key: &str) -> FLOAT {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+
+    for byte in key.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+
+    hash = (hash ^ (hash >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    hash = (hash ^ (hash >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    hash ^= hash >> 31;
+
+    ((hash >> 11) as f64 / (1u64 << 53) as f64) as FLOAT
+}
+
 #[export_module]
 pub mod array_functions {
     use rand::prelude::*;
-    use rhai::{Array, Dynamic, INT};
+    use rhai::{Array, Dynamic, EvalAltResult, FnPtr, Position, INT};
+
+    #[cfg(feature = "float")]
+    use rand_distr::StandardNormal;
+
+    #[cfg(feature = "float")]
+    use rhai::FLOAT;
 
     /// Copy a random element from the array and return it.
     /// Requires the `array` feature.
@@ -18,10 +84,10 @@ pub mod array_functions {
     ///
     /// print(`I'll give you a random number between 1 and 5: ${number}`);
     /// ```
-    #[rhai_fn(global)]
-    pub fn sample(array: &mut Array) -> Dynamic {
+    #[rhai_fn(global, volatile)]
+    pub fn sample(ctx: NativeCallContext, array: &mut Array) -> Dynamic {
         if !array.is_empty() {
-            let mut rng = rand::thread_rng();
+            let mut rng = crate::rng::rng(&ctx);
             if let Some(res) = array.choose(&mut rng) {
                 return res.clone();
             }
@@ -29,6 +95,129 @@ pub mod array_functions {
         Dynamic::UNIT
     }
 
+    /// Copy a random element from an array of arrays and return it, as if the nested arrays were
+    /// flattened into a single array.
+    /// Requires the `array` feature.
+    ///
+    /// Every nested element is equally likely to be chosen, so larger nested arrays are chosen
+    /// proportionally more often. The flattened array is never actually built.
+    ///
+    /// `()` is returned if there are no nested elements.
+    ///
+    /// Every element of the array must be an array.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let teams = [["Alice", "Bob"], ["Carol"], ["Dave", "Eve", "Frank"]];
+    ///
+    /// let player = teams.sample_flat();
+    ///
+    /// print(`A random player from any team: ${player}`);
+    /// ```
+    #[rhai_fn(global, return_raw, volatile)]
+    pub fn sample_flat(
+        ctx: NativeCallContext,
+        arrays: &mut Array,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        let nested = arrays
+            .iter()
+            .map(|array| array.read_lock::<Array>())
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| {
+                EvalAltResult::ErrorArithmetic(
+                    "Elements must all be arrays".to_string(),
+                    Position::NONE,
+                )
+            })?;
+
+        let total: usize = nested.iter().map(|array| array.len()).sum();
+
+        if total == 0 {
+            return Ok(Dynamic::UNIT);
+        }
+
+        let mut index = crate::rng::rng(&ctx).gen_range(0..total);
+
+        for array in &nested {
+            if index < array.len() {
+                return Ok(array[index].clone());
+            }
+            index -= array.len();
+        }
+
+        unreachable!("index is less than the total number of elements")
+    }
+
+    /// Return the index of a random element in the array.
+    /// Requires the `array` feature.
+    ///
+    /// `-1` is returned if the array is empty.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let names = ["Alice", "Bob", "Carol"];
+    /// let ages = [31, 25, 47];
+    ///
+    /// let i = names.sample_index();
+    ///
+    /// print(`${names[i]} is ${ages[i]} years old`);
+    /// ```
+    #[rhai_fn(global, volatile)]
+    pub fn sample_index(ctx: NativeCallContext, array: &mut Array) -> INT {
+        if array.is_empty() {
+            -1
+        } else {
+            crate::rng::rng(&ctx).gen_range(0..array.len()) as INT
+        }
+    }
+
+    /// Copy a random element from the array and return it, where each element is chosen with a
+    /// probability proportional to its corresponding weight.
+    /// Requires the `float` and `array` features.
+    ///
+    /// `()` is returned if the array is empty.
+    ///
+    /// `weights` must be an array of non-negative numbers with the same length as the array,
+    /// which must not all be zero (unless the array is empty).
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let loot = ["common", "rare", "legendary"];
+    ///
+    /// let item = loot.sample_weighted([90, 9, 1]);
+    ///
+    /// print(`You found a ${item} item!`);
+    /// ```
+    #[cfg(feature = "float")]
+    #[rhai_fn(global, return_raw, volatile)]
+    pub fn sample_weighted(
+        ctx: NativeCallContext,
+        array: &mut Array,
+        weights: Array,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        if weights.len() != array.len() {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "Number of weights ({}) does not match the number of elements ({})",
+                    weights.len(),
+                    array.len()
+                ),
+                Position::NONE,
+            )
+            .into());
+        }
+        if array.is_empty() {
+            return Ok(Dynamic::UNIT);
+        }
+
+        let index = crate::weights::from_array(&weights)?;
+
+        Ok(array[crate::rng::rng(&ctx).sample(&index)].clone())
+    }
+
     /// Copy a non-repeating random sample of elements from the array and return it.
     /// Requires the `array` feature.
     ///
@@ -46,13 +235,13 @@ pub mod array_functions {
     ///
     /// print(`I'll give you 3 random numbers between 1 and 5: ${samples}`);
     /// ```
-    #[rhai_fn(global, name = "sample")]
-    pub fn sample_with_amount(array: &mut Array, amount: INT) -> Array {
+    #[rhai_fn(global, name = "sample", volatile)]
+    pub fn sample_with_amount(ctx: NativeCallContext, array: &mut Array, amount: INT) -> Array {
         if array.is_empty() || amount <= 0 {
             return Array::new();
         }
 
-        let mut rng = rand::thread_rng();
+        let mut rng = crate::rng::rng(&ctx);
         let amount = amount as usize;
 
         if amount >= array.len() {
@@ -69,6 +258,44 @@ pub mod array_functions {
         }
     }
 
+    /// Copy a random sample of `amount` elements from the array, with repetition, and return it.
+    /// Requires the `array` feature.
+    ///
+    /// Unlike `sample`, the same element may be selected more than once, so `amount` can exceed
+    /// the length of the array.
+    ///
+    /// * If `amount` ≤ 0, the empty array is returned.
+    /// * If the array is empty, the empty array is returned.
+    /// * `amount` must not exceed 1,048,576.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let x = [1, 2, 3];
+    ///
+    /// let samples = x.sample_with_replacement(10);
+    ///
+    /// print(`I'll give you 10 random numbers between 1 and 3: ${samples}`);
+    /// ```
+    #[rhai_fn(global, return_raw, volatile)]
+    pub fn sample_with_replacement(
+        ctx: NativeCallContext,
+        array: &mut Array,
+        amount: INT,
+    ) -> Result<Array, Box<EvalAltResult>> {
+        crate::limits::check_array_length(amount)?;
+
+        if array.is_empty() || amount <= 0 {
+            return Ok(Array::new());
+        }
+
+        let mut rng = crate::rng::rng(&ctx);
+
+        Ok((0..amount)
+            .map(|_| array[rng.gen_range(0..array.len())].clone())
+            .collect())
+    }
+
     /// Shuffle the elements in the array.
     /// Requires the `array` feature.
     ///
@@ -79,9 +306,766 @@ pub mod array_functions {
     ///
     /// x.shuffle();    // shuffle the elements inside the array
     /// ```
-    #[rhai_fn(global)]
-    pub fn shuffle(array: &mut Array) {
-        let mut rng = rand::thread_rng();
+    #[rhai_fn(global, volatile)]
+    pub fn shuffle(ctx: NativeCallContext, array: &mut Array) {
+        let mut rng = crate::rng::rng(&ctx);
         array.shuffle(&mut rng);
     }
+
+    /// Shuffle the elements in the array deterministically, using a generator seeded with `seed`.
+    /// Requires the `array` feature.
+    ///
+    /// Shuffling the same array with the same `seed` always produces the same order. This does
+    /// not affect the sequence of values generated by other functions.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let x = [1, 2, 3, 4, 5];
+    ///
+    /// x.shuffle(42);  // always shuffles into the same order
+    /// ```
+    #[rhai_fn(global, name = "shuffle")]
+    pub fn shuffle_with_seed(array: &mut Array, seed: INT) {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(seed as u64);
+        array.shuffle(&mut rng);
+    }
+
+    /// Shuffle only the first `amount` elements of the array, so that they are a uniformly random
+    /// selection of all the elements in random order.
+    /// Requires the `array` feature.
+    ///
+    /// This is more efficient than a full `shuffle` when only the first few elements are needed.
+    /// The order of the remaining elements is unspecified.
+    ///
+    /// * If `amount` ≤ 0, the array is not changed.
+    /// * If `amount` ≥ length of array, the entire array is shuffled.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let deck = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
+    ///
+    /// deck.shuffle_partial(3);
+    ///
+    /// print(`Your hand: ${deck.extract(0, 3)}`);
+    /// ```
+    #[rhai_fn(global, volatile)]
+    pub fn shuffle_partial(ctx: NativeCallContext, array: &mut Array, amount: INT) {
+        let amount = (amount.max(0) as usize).min(array.len());
+        let mut rng = crate::rng::rng(&ctx);
+
+        // `SliceRandom::partial_shuffle` places the selection at the _end_ of the slice,
+        // so run the Fisher-Yates steps from the front instead
+        for i in 0..amount {
+            let j = rng.gen_range(i..array.len());
+            array.swap(i, j);
+        }
+    }
+
+    /// Partially shuffle the elements in the array by swapping `rounds` random pairs of elements.
+    /// Requires the `array` feature.
+    ///
+    /// A small number of `rounds` lightly perturbs the original order, while a large number
+    /// approaches a full shuffle.
+    ///
+    /// `rounds` must not be negative.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let x = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
+    ///
+    /// x.shuffle_rounds(2);    // at most 4 elements are moved
+    /// ```
+    #[rhai_fn(global, return_raw, volatile)]
+    pub fn shuffle_rounds(
+        ctx: NativeCallContext,
+        array: &mut Array,
+        rounds: INT,
+    ) -> Result<(), Box<EvalAltResult>> {
+        if rounds < 0 {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "Invalid number of rounds (must not be negative): {}",
+                    rounds
+                ),
+                Position::NONE,
+            )
+            .into());
+        }
+        if array.len() < 2 {
+            return Ok(());
+        }
+
+        let mut rng = crate::rng::rng(&ctx);
+
+        for _ in 0..rounds {
+            let i = rng.gen_range(0..array.len());
+            let j = rng.gen_range(0..array.len());
+            array.swap(i, j);
+        }
+
+        Ok(())
+    }
+
+    /// Select `k` distinct elements from the array, each chosen with a probability proportional
+    /// to `exp(score / temperature)` of its corresponding score.
+    /// Requires the `float` and `array` features.
+    ///
+    /// `scores` must be an array of finite numbers with the same length as the array.
+    /// Higher `temperature` values flatten the distribution, while lower values favor the
+    /// highest-scored elements more strongly. `temperature` must be positive.
+    ///
+    /// The selected elements are returned in the order they were picked.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let x = ["a", "b", "c", "d", "e"];
+    /// let scores = [1.0, 2.0, 3.0, 4.0, 5.0];
+    ///
+    /// let picks = x.stochastic_top_k(scores, 2, 1.0);
+    ///
+    /// print(`Two elements, most likely "d" and "e": ${picks}`);
+    /// ```
+    #[cfg(feature = "float")]
+    #[rhai_fn(global, return_raw, volatile)]
+    pub fn stochastic_top_k(
+        ctx: NativeCallContext,
+        array: &mut Array,
+        scores: Array,
+        k: INT,
+        temperature: FLOAT,
+    ) -> Result<Array, Box<EvalAltResult>> {
+        if scores.len() != array.len() {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "Number of scores ({}) does not match the number of elements ({})",
+                    scores.len(),
+                    array.len()
+                ),
+                Position::NONE,
+            )
+            .into());
+        }
+        if k < 0 || k as usize > array.len() {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "Invalid number of elements to select (must be between 0 and {}): {}",
+                    array.len(),
+                    k
+                ),
+                Position::NONE,
+            )
+            .into());
+        }
+        if !(temperature > 0.0 && temperature.is_finite()) {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Invalid temperature (must be positive): {}", temperature),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        let mut rng = crate::rng::rng(&ctx);
+
+        // Gumbel-top-k trick: perturbing each `score / temperature` with independent Gumbel
+        // noise and taking the `k` largest keys is equivalent to drawing `k` elements without
+        // replacement, each with probability proportional to `exp(score / temperature)`.
+        let mut keys = Vec::with_capacity(scores.len());
+
+        for (index, score) in scores.iter().enumerate() {
+            let score = super::to_float(score)
+                .filter(|score| score.is_finite())
+                .ok_or_else(|| {
+                    EvalAltResult::ErrorArithmetic(
+                        format!("Score must be a finite number: {}", score),
+                        Position::NONE,
+                    )
+                })?;
+            let u: FLOAT = rng.gen();
+            keys.push((score / temperature - (-u.ln()).ln(), index));
+        }
+
+        keys.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        Ok(keys
+            .into_iter()
+            .take(k as usize)
+            .map(|(_, index)| array[index].clone())
+            .collect())
+    }
+
+    /// Copy a random element from the array and return it, using a precomputed array of
+    /// cumulative weights.
+    /// Requires the `float` and `array` features.
+    ///
+    /// `cumulative` must be a non-decreasing array of finite, non-negative numbers with the same
+    /// length as the array, where each value is the running total of the weights up to and
+    /// including that element, as found in many loot and probability tables. The last value must
+    /// be positive.
+    ///
+    /// If the array is empty, `()` is returned.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let x = ["common", "rare", "epic"];
+    /// let cumulative = [70, 95, 100];     // weights of 70, 25 and 5
+    ///
+    /// let item = x.sample_with_cdf(cumulative);
+    ///
+    /// print(`You found a ${item} item!`);
+    /// ```
+    #[cfg(feature = "float")]
+    #[rhai_fn(global, return_raw, volatile)]
+    pub fn sample_with_cdf(
+        ctx: NativeCallContext,
+        array: &mut Array,
+        cumulative: Array,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        if cumulative.len() != array.len() {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "Number of cumulative weights ({}) does not match the number of elements ({})",
+                    cumulative.len(),
+                    array.len()
+                ),
+                Position::NONE,
+            )
+            .into());
+        }
+        if array.is_empty() {
+            return Ok(Dynamic::UNIT);
+        }
+
+        let mut weights = Vec::with_capacity(cumulative.len());
+        let mut previous = 0.0;
+
+        for value in &cumulative {
+            match super::to_float(value) {
+                Some(total) if total >= previous && total.is_finite() => {
+                    weights.push(total - previous);
+                    previous = total;
+                }
+                _ => {
+                    return Err(EvalAltResult::ErrorArithmetic(
+                        format!(
+                            "Cumulative weights must be finite and non-decreasing: {}",
+                            value
+                        ),
+                        Position::NONE,
+                    )
+                    .into())
+                }
+            }
+        }
+
+        let index = crate::weights::from_values(&weights)?;
+
+        Ok(array[crate::rng::rng(&ctx).sample(&index)].clone())
+    }
+
+    /// Copy a random element from the array that is not in the `recent` array, and return it.
+    /// Requires the `array` feature.
+    ///
+    /// This avoids repeating recently-used elements, e.g. for playlists or level rotations.
+    /// If every element is in `recent`, a random element from the entire array is returned instead.
+    ///
+    /// If the array is empty, `()` is returned.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let levels = ["forest", "desert", "castle", "cave"];
+    /// let recent = ["forest", "cave"];
+    ///
+    /// let next = levels.sample_avoid_recent(recent);
+    ///
+    /// print(`Next level (either desert or castle): ${next}`);
+    /// ```
+    #[rhai_fn(global, return_raw, volatile)]
+    pub fn sample_avoid_recent(
+        ctx: NativeCallContext,
+        array: &mut Array,
+        mut recent: Array,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        let mut candidates = Vec::with_capacity(array.len());
+
+        for (index, item) in array.iter().enumerate() {
+            if !super::contains(&ctx, &mut recent, item)? {
+                candidates.push(index);
+            }
+        }
+
+        let mut rng = crate::rng::rng(&ctx);
+
+        let index = if candidates.is_empty() {
+            (0..array.len()).choose(&mut rng)
+        } else {
+            candidates.choose(&mut rng).copied()
+        };
+
+        Ok(index.map_or(Dynamic::UNIT, |index| array[index].clone()))
+    }
+
+    /// Copy an element from the array, chosen by `weights`, deterministically for a `key`,
+    /// and return it.
+    /// Requires the `float` and `array` features.
+    ///
+    /// The `key` is hashed into a number that is used in place of a random draw, so the same
+    /// `key` always gives the same element, while different keys are spread across the elements
+    /// in proportion to their weights. This is useful for bucketing, e.g. feature flags by user ID.
+    ///
+    /// `weights` must be an array of non-negative numbers, with the same length as the array,
+    /// which must not all be zero.
+    ///
+    /// If the array is empty, `()` is returned.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let variants = ["control", "experiment"];
+    ///
+    /// let variant = variants.weighted_choice_by_key([90, 10], "user-12345");
+    ///
+    /// print(`This user always sees: ${variant}`);
+    /// ```
+    #[cfg(feature = "float")]
+    #[rhai_fn(global, return_raw, volatile)]
+    pub fn weighted_choice_by_key(
+        array: &mut Array,
+        weights: Array,
+        key: &str,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        if weights.len() != array.len() {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "Number of weights ({}) does not match the number of elements ({})",
+                    weights.len(),
+                    array.len()
+                ),
+                Position::NONE,
+            )
+            .into());
+        }
+        if array.is_empty() {
+            return Ok(Dynamic::UNIT);
+        }
+
+        let weights = weights
+            .iter()
+            .map(crate::weights::to_weight)
+            .collect::<Result<Vec<_>, _>>()?;
+        let probabilities = crate::weights::normalize(&weights)?;
+
+        let target = super::hash_to_unit(key);
+        let mut total = 0.0;
+
+        for (index, probability) in probabilities.iter().enumerate() {
+            total += probability;
+
+            if target < total && *probability > 0.0 {
+                return Ok(array[index].clone());
+            }
+        }
+
+        // Rounding errors may leave the total slightly below 1.0
+        let index = probabilities.iter().rposition(|&p| p > 0.0).unwrap();
+
+        Ok(array[index].clone())
+    }
+
+    /// Copy a non-repeating random sample of `amount` elements from a random contiguous window
+    /// of `window` elements in the array, and return it.
+    /// Requires the `array` feature.
+    ///
+    /// The window starts at a random position such that it fits within the array. If `window`
+    /// is larger than the array, the window is clamped to the entire array.
+    /// The selected elements are then sampled as with `sample(amount)`.
+    ///
+    /// `window` must be at least `1`.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let x = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
+    ///
+    /// let samples = x.sample_windowed(4, 2);
+    ///
+    /// print(`Two numbers that are at most 3 apart: ${samples}`);
+    /// ```
+    #[rhai_fn(global, return_raw, volatile)]
+    pub fn sample_windowed(
+        ctx: NativeCallContext,
+        array: &mut Array,
+        window: INT,
+        amount: INT,
+    ) -> Result<Array, Box<EvalAltResult>> {
+        if window < 1 {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Invalid window size (must be at least 1): {}", window),
+                Position::NONE,
+            )
+            .into());
+        }
+        if array.is_empty() {
+            return Ok(Array::new());
+        }
+
+        let window = (window as usize).min(array.len());
+        let start = crate::rng::rng(&ctx).gen_range(0..=array.len() - window);
+        let mut slice = array[start..start + window].to_vec();
+
+        Ok(sample_with_amount(ctx, &mut slice, amount))
+    }
+
+    /// Copy a balanced random sample of elements from the array and return it, taking
+    /// `per_class` elements from each group of elements that share the same label.
+    /// Requires the `array` feature.
+    ///
+    /// `labels` must be an array with the same length as the array, holding the label of each
+    /// element. Labels are compared with the `==` operator.
+    ///
+    /// Elements are sampled without repetition, so a group with fewer than `per_class`
+    /// elements contributes all of its elements. Groups appear in the result in the order
+    /// their labels first appear in `labels`, with the elements of each group in random order.
+    ///
+    /// * If `per_class` ≤ 0, the empty array is returned.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let images = ["cat1", "cat2", "cat3", "dog1", "dog2", "bird1"];
+    /// let labels = ["cat", "cat", "cat", "dog", "dog", "bird"];
+    ///
+    /// let batch = images.sample_balanced(labels, 2);
+    ///
+    /// print(`Two cats, two dogs and a bird: ${batch}`);
+    /// ```
+    #[rhai_fn(global, return_raw, volatile)]
+    pub fn sample_balanced(
+        ctx: NativeCallContext,
+        array: &mut Array,
+        labels: Array,
+        per_class: INT,
+    ) -> Result<Array, Box<EvalAltResult>> {
+        if labels.len() != array.len() {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "Number of labels ({}) does not match the number of elements ({})",
+                    labels.len(),
+                    array.len()
+                ),
+                Position::NONE,
+            )
+            .into());
+        }
+        if per_class <= 0 {
+            return Ok(Array::new());
+        }
+
+        let mut groups: Vec<(Dynamic, Vec<usize>)> = Vec::new();
+
+        for (index, label) in labels.iter().enumerate() {
+            let mut group = None;
+
+            for (position, (group_label, _)) in groups.iter_mut().enumerate() {
+                if super::equals(&ctx, group_label, label)? {
+                    group = Some(position);
+                    break;
+                }
+            }
+
+            match group {
+                Some(position) => groups[position].1.push(index),
+                None => groups.push((label.clone(), vec![index])),
+            }
+        }
+
+        let mut rng = crate::rng::rng(&ctx);
+        let mut result = Array::new();
+
+        for (_, mut indices) in groups {
+            let (chosen, _) = indices.partial_shuffle(&mut rng, per_class as usize);
+            result.extend(chosen.iter().map(|&index| array[index].clone()));
+        }
+
+        Ok(result)
+    }
+
+    /// Copy a random element from the array that satisfies a predicate, and return it.
+    /// Requires the `array` feature.
+    ///
+    /// `predicate` is called once on each element, and must return a boolean. Each matching
+    /// element is equally likely to be returned, without building an array of the matches.
+    ///
+    /// If no element matches, `()` is returned.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let x = [1, 2, 3, 4, 5, 6, 7, 8];
+    ///
+    /// let even = x.sample_where(|v| v % 2 == 0);
+    ///
+    /// print(`I'll give you a random even number: ${even}`);
+    /// ```
+    #[rhai_fn(global, return_raw, volatile)]
+    pub fn sample_where(
+        ctx: NativeCallContext,
+        array: &mut Array,
+        predicate: FnPtr,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        let mut rng = crate::rng::rng(&ctx);
+        let mut matches = 0;
+        let mut selected = None;
+
+        // Reservoir sampling with a reservoir of one element
+        for (index, item) in array.iter().enumerate() {
+            if predicate.call_within_context::<bool>(&ctx, (item.clone(),))? {
+                matches += 1;
+
+                if rng.gen_range(0..matches) == 0 {
+                    selected = Some(index);
+                }
+            }
+        }
+
+        Ok(selected.map_or(Dynamic::UNIT, |index| array[index].clone()))
+    }
+
+    /// Shuffle the elements in the array with a single riffle shuffle, as done by hand with a
+    /// deck of cards.
+    /// Requires the `float` and `array` features.
+    ///
+    /// The array is cut into two packets near the middle, which are then interleaved by
+    /// dropping elements from either packet with a probability proportional to its size
+    /// (the Gilbert-Shannon-Reeds model). `imperfection` controls how far the cut may be from
+    /// the middle: `0.0` always cuts exactly in the middle, while `1.0` cuts as unevenly as a
+    /// typical human would.
+    ///
+    /// Unlike `shuffle`, a single riffle shuffle leaves much of the original order intact;
+    /// about seven are needed to mix a deck of 52 cards well.
+    ///
+    /// `imperfection` must be between `0.0` and `1.0` (inclusive).
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let deck = [];
+    ///
+    /// for card in 0..52 {
+    ///     deck.push(card);
+    /// }
+    ///
+    /// for i in 0..7 {
+    ///     deck.riffle_shuffle(0.5);
+    /// }
+    /// ```
+    #[cfg(feature = "float")]
+    #[rhai_fn(global, return_raw, volatile)]
+    pub fn riffle_shuffle(
+        ctx: NativeCallContext,
+        array: &mut Array,
+        imperfection: FLOAT,
+    ) -> Result<(), Box<EvalAltResult>> {
+        if !(0.0..=1.0).contains(&imperfection) {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "Invalid imperfection (must be between 0.0 and 1.0): {}",
+                    imperfection
+                ),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        let mut rng = crate::rng::rng(&ctx);
+        let len = array.len();
+
+        // The cut deviates from the middle like a binomial split, scaled by `imperfection`
+        let deviation: FLOAT = rng.sample(StandardNormal);
+        let cut = len as FLOAT / 2.0 + imperfection * deviation * (len as FLOAT).sqrt() / 2.0;
+        let cut = (cut.round().max(0.0) as usize).min(len);
+
+        let mut right = array.split_off(cut).into_iter().peekable();
+        let mut left = std::mem::take(array).into_iter().peekable();
+        let (mut left_len, mut right_len) = (cut, len - cut);
+
+        while left_len + right_len > 0 {
+            if rng.gen_range(0..left_len + right_len) < left_len {
+                array.extend(left.next());
+                left_len -= 1;
+            } else {
+                array.extend(right.next());
+                right_len -= 1;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Copy a random sample of `amount` elements from the array, with repetition, biased towards
+    /// the front of the array by a power law, and return it.
+    /// Requires the `float` and `array` features.
+    ///
+    /// Each pick selects the element at `index` with a probability proportional to
+    /// `1 / (index + 1) ** alpha`. An `alpha` of `0.0` selects uniformly, while larger values
+    /// concentrate selections more heavily on the first elements.
+    ///
+    /// If the array is empty, the empty array is returned.
+    ///
+    /// `amount` and `alpha` must not be negative, and `amount` must not exceed 1,048,576.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let posts = ["viral", "popular", "average", "niche", "obscure"];
+    ///
+    /// let views = posts.sample_power_biased(10, 1.5);
+    ///
+    /// print(`Posts viewed: ${views}`);
+    /// ```
+    #[cfg(feature = "float")]
+    #[rhai_fn(global, return_raw, volatile)]
+    pub fn sample_power_biased(
+        ctx: NativeCallContext,
+        array: &mut Array,
+        amount: INT,
+        alpha: FLOAT,
+    ) -> Result<Array, Box<EvalAltResult>> {
+        if amount < 0 {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "Invalid number of elements to select (must not be negative): {}",
+                    amount
+                ),
+                Position::NONE,
+            )
+            .into());
+        }
+        crate::limits::check_array_length(amount)?;
+        if alpha < 0.0 || !alpha.is_finite() {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Invalid exponent (must not be negative): {}", alpha),
+                Position::NONE,
+            )
+            .into());
+        }
+        if array.is_empty() {
+            return Ok(Array::new());
+        }
+
+        let weights: Vec<FLOAT> = (1..=array.len())
+            .map(|rank| (rank as FLOAT).powf(-alpha))
+            .collect();
+        let index = crate::weights::from_values(&weights)?;
+        let mut rng = crate::rng::rng(&ctx);
+
+        Ok((0..amount)
+            .map(|_| array[rng.sample(&index)].clone())
+            .collect())
+    }
+
+    /// Rotate the elements in the array to the left by a random offset, where each offset is
+    /// chosen with a probability proportional to its corresponding weight.
+    /// Requires the `float` and `array` features.
+    ///
+    /// With an offset of `k`, the element at index `k` becomes the first element, and the
+    /// elements before it wrap around to the end. This is useful for weighted round-robin
+    /// scheduling.
+    ///
+    /// `weights` must be an array of non-negative numbers with the same length as the array,
+    /// which must not all be zero (unless the array is empty).
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let servers = ["a", "b", "c", "d"];
+    ///
+    /// servers.weighted_rotate([4, 2, 1, 1]);
+    ///
+    /// print(`Servers in order of preference: ${servers}`);
+    /// ```
+    #[cfg(feature = "float")]
+    #[rhai_fn(global, return_raw, volatile)]
+    pub fn weighted_rotate(
+        ctx: NativeCallContext,
+        array: &mut Array,
+        weights: Array,
+    ) -> Result<(), Box<EvalAltResult>> {
+        if weights.len() != array.len() {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "Number of weights ({}) does not match the number of elements ({})",
+                    weights.len(),
+                    array.len()
+                ),
+                Position::NONE,
+            )
+            .into());
+        }
+        if array.is_empty() {
+            return Ok(());
+        }
+
+        let index = crate::weights::from_array(&weights)?;
+        let offset = crate::rng::rng(&ctx).sample(&index);
+
+        array.rotate_left(offset);
+
+        Ok(())
+    }
+
+    /// Copy a random element from the array and return it, favoring elements near the end
+    /// of the array.
+    /// Requires the `float` and `array` features.
+    ///
+    /// Each element is `ratio` times as likely to be chosen as the element before it, so
+    /// the most recently added elements are the most likely to be chosen.
+    ///
+    /// The array must not be empty, and `ratio` must be greater than `1.0`.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let pages = ["home", "search", "results", "product"];   // in the order visited
+    ///
+    /// let page = pages.sample_recency(2.0);
+    ///
+    /// print(`Most likely a recently visited page: ${page}`);
+    /// ```
+    #[cfg(feature = "float")]
+    #[rhai_fn(global, return_raw, volatile)]
+    pub fn sample_recency(
+        ctx: NativeCallContext,
+        array: &mut Array,
+        ratio: FLOAT,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        if array.is_empty() {
+            return Err(EvalAltResult::ErrorArithmetic(
+                "Array must not be empty".to_string(),
+                Position::NONE,
+            )
+            .into());
+        }
+        if !(ratio > 1.0 && ratio.is_finite()) {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Invalid ratio (must be greater than 1.0): {}", ratio),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        // Weights are relative to the last element, so they underflow rather than overflow
+        let last = array.len() - 1;
+        let weights: Vec<FLOAT> = (0..array.len())
+            .map(|i| ratio.powi(-((last - i).min(i32::MAX as usize) as i32)))
+            .collect();
+        let index = crate::weights::from_values(&weights)?;
+
+        Ok(array[crate::rng::rng(&ctx).sample(&index)].clone())
+    }
 }
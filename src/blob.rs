@@ -0,0 +1,82 @@
+#[allow(unused_imports)]
+use rhai::plugin::*;
+
+#[export_module]
+pub mod blob_functions {
+    use rand::prelude::*;
+    use rhai::{Blob, Dynamic, INT};
+
+    /// Return a random byte from the BLOB as an integer.
+    /// Requires the `array` feature.
+    ///
+    /// `()` is returned if the BLOB is empty.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let x = blob(10, 42);
+    ///
+    /// let byte = x.sample();
+    ///
+    /// print(`I'll give you a random byte from the BLOB: ${byte}`);
+    /// ```
+    #[rhai_fn(global, volatile)]
+    pub fn sample(ctx: NativeCallContext, blob: &mut Blob) -> Dynamic {
+        let mut rng = crate::rng::rng(&ctx);
+
+        match blob.choose(&mut rng) {
+            Some(&byte) => (byte as INT).into(),
+            None => Dynamic::UNIT,
+        }
+    }
+
+    /// Copy a non-repeating random sample of bytes from the BLOB and return it as a new BLOB.
+    /// Requires the `array` feature.
+    ///
+    /// Bytes in the returned BLOB are likely not in the same order as in the original BLOB.
+    ///
+    /// * If `amount` ≤ 0, the empty BLOB is returned.
+    /// * If `amount` ≥ length of BLOB, the entire BLOB is returned, but shuffled.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let x = blob(10, 42);
+    ///
+    /// let samples = x.sample(3);
+    ///
+    /// print(`I'll give you 3 random bytes from the BLOB: ${samples}`);
+    /// ```
+    #[rhai_fn(global, name = "sample", volatile)]
+    pub fn sample_with_amount(ctx: NativeCallContext, blob: &mut Blob, amount: INT) -> Blob {
+        if blob.is_empty() || amount <= 0 {
+            return Blob::new();
+        }
+
+        let mut rng = crate::rng::rng(&ctx);
+        let amount = (amount as usize).min(blob.len());
+
+        // `choose_multiple` does not randomize the order of the selected bytes
+        let mut res: Blob = blob.choose_multiple(&mut rng, amount).copied().collect();
+        res.shuffle(&mut rng);
+        res
+    }
+
+    /// Shuffle the bytes in the BLOB.
+    /// Requires the `array` feature.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let x = blob(10, 42);
+    ///
+    /// for i in 0..x.len() { x[i] = i; }
+    ///
+    /// x.shuffle();    // shuffle the bytes inside the BLOB
+    /// ```
+    #[rhai_fn(global, volatile)]
+    pub fn shuffle(ctx: NativeCallContext, blob: &mut Blob) {
+        let mut rng = crate::rng::rng(&ctx);
+        blob.shuffle(&mut rng);
+    }
+}
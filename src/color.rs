@@ -0,0 +1,248 @@
+#[allow(unused_imports)]
+use rhai::plugin::*;
+
+use rhai::FLOAT;
+
+use rhai::{EvalAltResult, Position};
+
+/// Format an RGB color as a `#RRGGBB` hex string.
+fn to_hex((r, g, b): (u8, u8, u8)) -> String {
+    format!("#{:02X}{:02X}{:02X}", r, g, b)
+}
+
+/// Parse a `#RRGGBB` hex string into an RGB color.
+fn parse_hex(color: &str) -> Result<(u8, u8, u8), Box<EvalAltResult>> {
+    let digits = color
+        .strip_prefix('#')
+        .filter(|d| d.len() == 6 && d.bytes().all(|b| b.is_ascii_hexdigit()))
+        .ok_or_else(|| {
+            EvalAltResult::ErrorArithmetic(
+                format!("Invalid color (must be a #RRGGBB hex string): {}", color),
+                Position::NONE,
+            )
+        })?;
+
+    let channel = |i: usize| u8::from_str_radix(&digits[i..i + 2], 16).unwrap();
+
+    Ok((channel(0), channel(2), channel(4)))
+}
+
+/// Convert an RGB color into hue (in degrees, between `0.0` and `360.0`), saturation and value
+/// (both between `0.0` and `1.0`).
+#[cfg(feature = "array")]
+fn rgb_to_hsv((r, g, b): (u8, u8, u8)) -> (FLOAT, FLOAT, FLOAT) {
+    let (r, g, b) = (r as FLOAT / 255.0, g as FLOAT / 255.0, b as FLOAT / 255.0);
+    let max = r.max(g).max(b);
+    let delta = max - r.min(g).min(b);
+
+    let hue = if delta == 0.0 {
+        0.0
+    } else if max == r {
+        60.0 * ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / delta + 2.0)
+    } else {
+        60.0 * ((r - g) / delta + 4.0)
+    };
+    let saturation = if max == 0.0 { 0.0 } else { delta / max };
+
+    (hue, saturation, max)
+}
+
+/// Convert hue (in degrees), saturation and value (both between `0.0` and `1.0`) into an RGB color.
+#[cfg(feature = "array")]
+fn hsv_to_rgb(hue: FLOAT, saturation: FLOAT, value: FLOAT) -> (u8, u8, u8) {
+    let hue = hue.rem_euclid(360.0) / 60.0;
+    let chroma = value * saturation;
+    let x = chroma * (1.0 - (hue.rem_euclid(2.0) - 1.0).abs());
+
+    let (r, g, b) = match hue as u8 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+
+    let m = value - chroma;
+    let channel = |v: FLOAT| ((v + m) * 255.0).clamp(0.0, 255.0).round() as u8;
+
+    (channel(r), channel(g), channel(b))
+}
+
+/// Approximate the RGB color of a blackbody at a temperature (in Kelvin).
+///
+/// Based on Tanner Helland's curve fit, which is reasonably accurate between 1000K and 40000K.
+fn blackbody_rgb(kelvin: FLOAT) -> (u8, u8, u8) {
+    let t = kelvin / 100.0;
+
+    let r = if t <= 66.0 {
+        255.0
+    } else {
+        329.698_727_446 * (t - 60.0).powf(-0.133_204_759_2)
+    };
+    let g = if t <= 66.0 {
+        99.470_802_586_1 * t.ln() - 161.119_568_166_1
+    } else {
+        288.122_169_528_3 * (t - 60.0).powf(-0.075_514_849_2)
+    };
+    let b = if t >= 66.0 {
+        255.0
+    } else if t <= 19.0 {
+        0.0
+    } else {
+        138.517_731_223_1 * (t - 10.0).ln() - 305.044_792_730_7
+    };
+
+    let clamp = |v: FLOAT| v.clamp(0.0, 255.0).round() as u8;
+
+    (clamp(r), clamp(g), clamp(b))
+}
+
+#[export_module]
+pub mod color_functions {
+    use rand::prelude::*;
+    use rhai::{EvalAltResult, ImmutableString, Position, FLOAT};
+
+    #[cfg(feature = "array")]
+    use rhai::{Array, INT};
+
+    /// Lowest color temperature (in Kelvin) supported by the blackbody approximation.
+    const MIN_KELVIN: FLOAT = 1000.0;
+    /// Highest color temperature (in Kelvin) supported by the blackbody approximation.
+    const MAX_KELVIN: FLOAT = 40000.0;
+
+    /// Generate a random color, as a `#RRGGBB` hex string, along the blackbody curve for a
+    /// random temperature (in Kelvin) between `min_k` and `max_k` (inclusive).
+    /// Requires the `float` feature.
+    ///
+    /// Both temperatures must be between `1000.0` and `40000.0`, where low temperatures
+    /// give warm (red/orange) colors and high temperatures give cool (blue) colors.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let color = rand_color_temperature(2700.0, 6500.0);
+    ///
+    /// print(`I'll give you a random light color: ${color}`);
+    /// ```
+    #[rhai_fn(return_raw, volatile)]
+    pub fn rand_color_temperature(
+        ctx: NativeCallContext,
+        min_k: FLOAT,
+        max_k: FLOAT,
+    ) -> Result<ImmutableString, Box<EvalAltResult>> {
+        if !(MIN_KELVIN..=MAX_KELVIN).contains(&min_k)
+            || !(MIN_KELVIN..=MAX_KELVIN).contains(&max_k)
+        {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "Invalid color temperature (must be between {} and {}): {}..{}",
+                    MIN_KELVIN, MAX_KELVIN, min_k, max_k
+                ),
+                Position::NONE,
+            )
+            .into());
+        }
+        if min_k > max_k {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Range is empty: {}..{}", min_k, max_k),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        let kelvin = crate::rng::rng(&ctx).gen_range(min_k..=max_k);
+
+        Ok(super::to_hex(super::blackbody_rgb(kelvin)).into())
+    }
+
+    /// Generate an array of `count` random colors, as `#RRGGBB` hex strings, with hues within
+    /// `spread` degrees of the hue of the `base` color, forming an analogous color scheme.
+    /// Requires the `float` and `array` features.
+    ///
+    /// The saturation and brightness of each color are kept close to those of `base`.
+    ///
+    /// `base` must be a `#RRGGBB` hex string, `count` must not be negative or exceed 1,048,576,
+    /// and `spread` must be between `0.0` and `180.0` (inclusive).
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let palette = analogous_colors("#3366CC", 4, 30.0);
+    ///
+    /// print(`Colors that go well with blue: ${palette}`);
+    /// ```
+    #[cfg(feature = "array")]
+    #[rhai_fn(return_raw, volatile)]
+    pub fn analogous_colors(
+        ctx: NativeCallContext,
+        base: &str,
+        count: INT,
+        spread: FLOAT,
+    ) -> Result<Array, Box<EvalAltResult>> {
+        /// Maximum change in saturation and brightness from the base color.
+        const JITTER: FLOAT = 0.05;
+
+        let (hue, saturation, value) = super::rgb_to_hsv(super::parse_hex(base)?);
+
+        if count < 0 {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Invalid number of colors (must not be negative): {}", count),
+                Position::NONE,
+            )
+            .into());
+        }
+        crate::limits::check_array_length(count)?;
+        if !(0.0..=180.0).contains(&spread) {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Invalid spread (must be between 0.0 and 180.0): {}", spread),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        let mut rng = crate::rng::rng(&ctx);
+
+        Ok((0..count)
+            .map(|_| {
+                let hue = hue + rng.gen_range(-spread..=spread);
+                let saturation = (saturation + rng.gen_range(-JITTER..=JITTER)).clamp(0.0, 1.0);
+                let value = (value + rng.gen_range(-JITTER..=JITTER)).clamp(0.0, 1.0);
+
+                super::to_hex(super::hsv_to_rgb(hue, saturation, value)).into()
+            })
+            .collect())
+    }
+
+    /// Generate a random blend of two colors, given as `#RRGGBB` hex strings, and return it
+    /// as a `#RRGGBB` hex string.
+    /// Requires the `float` feature.
+    ///
+    /// A random blend factor is chosen, and each of the red, green and blue channels is
+    /// interpolated by that factor, so every channel of the result lies between the
+    /// corresponding channels of `a` and `b`.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let color = mix_colors("#FF0000", "#0000FF");
+    ///
+    /// print(`Somewhere between red and blue: ${color}`);
+    /// ```
+    #[rhai_fn(return_raw, volatile)]
+    pub fn mix_colors(
+        ctx: NativeCallContext,
+        a: &str,
+        b: &str,
+    ) -> Result<ImmutableString, Box<EvalAltResult>> {
+        let (ar, ag, ab) = super::parse_hex(a)?;
+        let (br, bg, bb) = super::parse_hex(b)?;
+
+        let t: FLOAT = crate::rng::rng(&ctx).gen();
+        let mix = |x: u8, y: u8| (x as FLOAT + t * (y as FLOAT - x as FLOAT)).round() as u8;
+
+        Ok(super::to_hex((mix(ar, br), mix(ag, bg), mix(ab, bb))).into())
+    }
+}
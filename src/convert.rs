@@ -0,0 +1,11 @@
+//! Conversions of script values shared by the Rhai modules.
+
+use rhai::{Dynamic, FLOAT};
+
+/// Convert a numeric value (integer or floating-point) into a `FLOAT`.
+pub fn to_float(value: &Dynamic) -> Option<FLOAT> {
+    value
+        .as_float()
+        .ok()
+        .or_else(|| value.as_int().ok().map(|v| v as FLOAT))
+}
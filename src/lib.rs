@@ -68,6 +68,31 @@
 //! # }
 //! ```
 //!
+//! ### Reproducible random numbers
+//!
+//! Use [`RandomPackage::with_seed`] (or call `rand_seed` in a script) to generate the same
+//! sequence of random values on every run, e.g. for tests and golden-file fixtures.
+//!
+//! ```rust
+//! # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+//! use rhai::Engine;
+//! use rhai::packages::Package;
+//!
+//! use rhai_rand::RandomPackage;
+//!
+//! let mut engine = Engine::new();
+//!
+//! engine.register_global_module(RandomPackage::with_seed(42).as_shared_module());
+//!
+//! let first = engine.eval::<i64>("rand()")?;
+//!
+//! engine.run("rand_seed(42)")?;
+//!
+//! assert_eq!(engine.eval::<i64>("rand()")?, first);
+//! # Ok(())
+//! # }
+//! ```
+//!
 //! ## Features
 //!
 #![cfg_attr(feature = "document-features", doc = document_features::document_features!(feature_label = "<span id=\"feature-{feature}\">**`{feature}`**</span>"))]
@@ -87,14 +112,65 @@ use rhai::plugin::*;
 
 #[cfg(feature = "array")]
 mod array;
+#[cfg(feature = "array")]
+mod blob;
+#[cfg(feature = "float")]
+mod color;
+#[cfg(feature = "float")]
+mod convert;
+mod limits;
+mod map;
+#[cfg(feature = "pattern")]
+mod pattern;
 mod rand;
+mod rng;
+#[cfg(feature = "uuid")]
+mod uuid;
+#[cfg(all(feature = "float", feature = "array"))]
+mod weights;
 
 def_package! {
     /// Package for random number generation, sampling and shuffling.
+    ///
+    /// Each instance of the package has its own random number generator. [`RandomPackage::new`]
+    /// does not seed it, so values are generated non-deterministically until a script calls
+    /// `rand_seed`.
     pub RandomPackage(lib) {
+        rng::install(lib);
+
         combine_with_exported_module!(lib, "rand", rand::rand_functions);
 
         #[cfg(feature = "array")]
         combine_with_exported_module!(lib, "array", array::array_functions);
+
+        #[cfg(feature = "array")]
+        combine_with_exported_module!(lib, "blob", blob::blob_functions);
+
+        combine_with_exported_module!(lib, "map", map::map_functions);
+
+        #[cfg(feature = "float")]
+        combine_with_exported_module!(lib, "color", color::color_functions);
+
+        #[cfg(feature = "pattern")]
+        combine_with_exported_module!(lib, "pattern", pattern::pattern_functions);
+
+        #[cfg(feature = "uuid")]
+        combine_with_exported_module!(lib, "uuid", uuid::uuid_functions);
+    }
+}
+
+impl RandomPackage {
+    /// Create a new `RandomPackage` whose functions generate a reproducible sequence of values
+    /// from `seed`.
+    ///
+    /// The seeded generator belongs to the returned package only, so it does not affect other
+    /// instances of `RandomPackage`, including those created later with [`RandomPackage::new`].
+    /// Scripts can re-seed it with `rand_seed`, or switch back to non-deterministic values with
+    /// `rand_clear_seed`.
+    #[must_use]
+    pub fn with_seed(seed: u64) -> Self {
+        let package = Self::new();
+        rng::seed_module(&package.0, seed);
+        package
     }
 }
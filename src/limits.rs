@@ -0,0 +1,26 @@
+//! Limits on the size of values generated by the package.
+//!
+//! Generating a huge string, BLOB or array would abort the host process when it runs out of
+//! memory, so functions that generate them check the requested length against [`MAX_LENGTH`]
+//! first and return an error instead.
+
+use rhai::INT;
+#[cfg(feature = "array")]
+use rhai::{EvalAltResult, Position};
+
+/// Maximum length of strings, BLOB's and arrays generated by the package.
+pub const MAX_LENGTH: INT = 1 << 20;
+
+/// Check that the length of a generated array does not exceed [`MAX_LENGTH`].
+#[cfg(feature = "array")]
+pub fn check_array_length(n: INT) -> Result<(), Box<EvalAltResult>> {
+    if n > MAX_LENGTH {
+        Err(EvalAltResult::ErrorArithmetic(
+            format!("Array length is too large (maximum {}): {}", MAX_LENGTH, n),
+            Position::NONE,
+        )
+        .into())
+    } else {
+        Ok(())
+    }
+}
@@ -0,0 +1,58 @@
+#[allow(unused_imports)]
+use rhai::plugin::*;
+
+#[export_module]
+pub mod map_functions {
+    use rand::prelude::*;
+    use rhai::{Dynamic, Map};
+
+    /// Return a random property name from the object map.
+    ///
+    /// `()` is returned if the object map is empty.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let themes = #{ dark: "#000000", light: "#FFFFFF", sepia: "#704214" };
+    ///
+    /// let name = themes.sample();
+    ///
+    /// print(`Today's theme is ${name}: ${themes[name]}`);
+    /// ```
+    #[rhai_fn(global, volatile)]
+    pub fn sample(ctx: NativeCallContext, map: &mut Map) -> Dynamic {
+        if map.is_empty() {
+            return Dynamic::UNIT;
+        }
+
+        let keys: Vec<_> = map.keys().collect();
+        let index = crate::rng::rng(&ctx).gen_range(0..keys.len());
+
+        keys[index].clone().into()
+    }
+
+    /// Copy a random property value from the object map and return it.
+    ///
+    /// `()` is returned if the object map is empty.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let prices = #{ apple: 1.2, banana: 0.5, cherry: 3.0 };
+    ///
+    /// let price = prices.sample_value();
+    ///
+    /// print(`A random price: ${price}`);
+    /// ```
+    #[rhai_fn(global, volatile)]
+    pub fn sample_value(ctx: NativeCallContext, map: &mut Map) -> Dynamic {
+        if map.is_empty() {
+            return Dynamic::UNIT;
+        }
+
+        let values: Vec<_> = map.values().collect();
+        let index = crate::rng::rng(&ctx).gen_range(0..values.len());
+
+        values[index].clone()
+    }
+}
@@ -0,0 +1,246 @@
+#[allow(unused_imports)]
+use rhai::plugin::*;
+
+use rhai::{EvalAltResult, Position};
+
+/// Maximum length of a string generated from a pattern.
+const MAX_LENGTH: usize = 1 << 16;
+
+/// A single element of a pattern.
+enum Atom {
+    /// A literal character.
+    Literal(char),
+    /// A character class, as a list of inclusive ranges of characters.
+    Class(Vec<(char, char)>),
+}
+
+fn pattern_error(message: String) -> Box<EvalAltResult> {
+    EvalAltResult::ErrorArithmetic(message, Position::NONE).into()
+}
+
+/// Parse a character class, after the opening `[`, up to and including the closing `]`.
+fn parse_class(
+    chars: &mut std::iter::Peekable<std::str::Chars>,
+) -> Result<Atom, Box<EvalAltResult>> {
+    let mut ranges = Vec::new();
+
+    if chars.peek() == Some(&'^') {
+        return Err(pattern_error(
+            "Negated character classes are not supported in pattern".to_string(),
+        ));
+    }
+
+    loop {
+        let start = match chars.next() {
+            Some(']') => break,
+            Some('\\') => chars
+                .next()
+                .ok_or_else(|| pattern_error("Incomplete escape in pattern".to_string()))?,
+            Some(ch) => ch,
+            None => {
+                return Err(pattern_error(
+                    "Unclosed character class in pattern".to_string(),
+                ))
+            }
+        };
+
+        let end = if chars.peek() == Some(&'-') {
+            chars.next();
+
+            match chars.next() {
+                // A trailing `-` is a literal, e.g. `[a-]`
+                Some(']') => {
+                    ranges.push((start, start));
+                    ranges.push(('-', '-'));
+                    break;
+                }
+                Some('\\') => chars
+                    .next()
+                    .ok_or_else(|| pattern_error("Incomplete escape in pattern".to_string()))?,
+                Some(ch) => ch,
+                None => {
+                    return Err(pattern_error(
+                        "Unclosed character class in pattern".to_string(),
+                    ))
+                }
+            }
+        } else {
+            start
+        };
+
+        if start > end {
+            return Err(pattern_error(format!(
+                "Invalid character range in pattern: {}-{}",
+                start, end
+            )));
+        }
+        if start <= '\u{D7FF}' && end >= '\u{E000}' {
+            return Err(pattern_error(format!(
+                "Character range in pattern must not span surrogate code points: {}-{}",
+                start, end
+            )));
+        }
+
+        ranges.push((start, end));
+    }
+
+    if ranges.is_empty() {
+        return Err(pattern_error(
+            "Empty character class in pattern".to_string(),
+        ));
+    }
+
+    Ok(Atom::Class(ranges))
+}
+
+/// Parse a `{n}` repetition count, after the opening `{`, up to and including the closing `}`.
+fn parse_count(
+    chars: &mut std::iter::Peekable<std::str::Chars>,
+) -> Result<usize, Box<EvalAltResult>> {
+    let mut digits = String::new();
+
+    loop {
+        match chars.next() {
+            Some('}') => break,
+            Some(ch) if ch.is_ascii_digit() => digits.push(ch),
+            Some(ch) => {
+                return Err(pattern_error(format!(
+                    "Unsupported repetition in pattern (only {{n}} is supported): {}",
+                    ch
+                )))
+            }
+            None => return Err(pattern_error("Unclosed repetition in pattern".to_string())),
+        }
+    }
+
+    digits.parse().map_err(|_| {
+        pattern_error(format!(
+            "Invalid repetition count in pattern: {{{}}}",
+            digits
+        ))
+    })
+}
+
+/// Parse a pattern into a list of atoms, each with its repetition count.
+fn parse_pattern(pattern: &str) -> Result<Vec<(Atom, usize)>, Box<EvalAltResult>> {
+    let mut atoms: Vec<(Atom, usize)> = Vec::new();
+    let mut chars = pattern.chars().peekable();
+    let mut repeatable = false;
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '[' => atoms.push((parse_class(&mut chars)?, 1)),
+            '{' => {
+                let count = parse_count(&mut chars)?;
+
+                match atoms.last_mut() {
+                    Some((_, n)) if repeatable => *n = count,
+                    _ => {
+                        return Err(pattern_error(
+                            "Repetition in pattern must follow a character or character class"
+                                .to_string(),
+                        ))
+                    }
+                }
+
+                repeatable = false;
+                continue;
+            }
+            '\\' => {
+                let ch = chars
+                    .next()
+                    .ok_or_else(|| pattern_error("Incomplete escape in pattern".to_string()))?;
+                atoms.push((Atom::Literal(ch), 1));
+            }
+            ']' | '}' | '(' | ')' | '|' | '*' | '+' | '?' | '.' | '^' | '$' => {
+                return Err(pattern_error(format!(
+                    "Unsupported syntax in pattern (use \\{} for a literal): {}",
+                    ch, ch
+                )))
+            }
+            ch => atoms.push((Atom::Literal(ch), 1)),
+        }
+
+        repeatable = true;
+    }
+
+    let length = atoms
+        .iter()
+        .try_fold(0_usize, |total, (_, n)| total.checked_add(*n));
+
+    match length {
+        Some(length) if length <= MAX_LENGTH => (),
+        _ => {
+            return Err(pattern_error(format!(
+                "Pattern generates a string that is too long (maximum {})",
+                MAX_LENGTH
+            )))
+        }
+    }
+
+    Ok(atoms)
+}
+
+#[export_module]
+pub mod pattern_functions {
+    use rand::prelude::*;
+    use rhai::{EvalAltResult, ImmutableString};
+
+    /// Generate a random string following a simple regex-like pattern.
+    /// Requires the `pattern` feature.
+    ///
+    /// The following syntax is supported:
+    ///
+    /// * `[...]` - a character class, matching any one of the characters listed,
+    ///   with ranges such as `A-Z`
+    /// * `{n}` - repeat the preceding character or character class exactly `n` times
+    /// * `\` - escape the following character, to use it literally
+    /// * any other character is used literally
+    ///
+    /// Any other regex syntax (e.g. `*`, `+`, `?`, `|`, groups) is not supported
+    /// and results in an error.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let plate = rand_pattern("[A-Z]{3}-[0-9]{4}");
+    ///
+    /// print(`Your license plate is: ${plate}`);
+    /// ```
+    #[rhai_fn(return_raw, volatile)]
+    pub fn rand_pattern(
+        ctx: NativeCallContext,
+        pattern: &str,
+    ) -> Result<ImmutableString, Box<EvalAltResult>> {
+        let atoms = super::parse_pattern(pattern)?;
+        let mut rng = crate::rng::rng(&ctx);
+        let mut result = String::new();
+
+        for (atom, count) in &atoms {
+            for _ in 0..*count {
+                match atom {
+                    super::Atom::Literal(ch) => result.push(*ch),
+                    super::Atom::Class(ranges) => {
+                        let total: u64 = ranges
+                            .iter()
+                            .map(|&(start, end)| end as u64 - start as u64 + 1)
+                            .sum();
+                        let mut offset = rng.gen_range(0..total);
+
+                        for &(start, end) in ranges {
+                            let size = end as u64 - start as u64 + 1;
+
+                            if offset < size {
+                                result.push(char::from_u32(start as u32 + offset as u32).unwrap());
+                                break;
+                            }
+                            offset -= size;
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(result.into())
+    }
+}
@@ -1,18 +1,467 @@
 #[allow(unused_imports)]
 use rhai::plugin::*;
 
+#[cfg(feature = "float")]
+use crate::convert::to_float;
+#[cfg(feature = "float")]
+use rhai::{FLOAT, INT};
+
+#[cfg(feature = "array")]
+use crate::limits::check_array_length;
+use crate::limits::MAX_LENGTH;
+
+#[cfg(feature = "float")]
+const PI: FLOAT = std::f64::consts::PI as FLOAT;
+
+/// Convert an array of numbers into a vector of `FLOAT`'s with exactly `dimensions` components.
+#[cfg(all(feature = "float", feature = "array"))]
+fn to_vector(value: &Dynamic, dimensions: usize) -> Option<Vec<FLOAT>> {
+    let array = value.read_lock::<rhai::Array>()?;
+
+    if array.len() != dimensions {
+        return None;
+    }
+
+    array.iter().map(to_float).collect()
+}
+
+/// Compute the radical inverse of `index` in `base`, i.e. the `index`-th value of the
+/// Halton sequence for that base.
+#[cfg(feature = "float")]
+fn radical_inverse(mut index: INT, base: INT) -> FLOAT {
+    let mut result = 0.0;
+    let mut fraction = 1.0 / base as FLOAT;
+
+    while index > 0 {
+        result += (index % base) as FLOAT * fraction;
+        index /= base;
+        fraction /= base as FLOAT;
+    }
+
+    result
+}
+
+/// Compute the total weight of a node in a tree of weights, where leaves are numeric weights
+/// and branches are object maps.
+#[cfg(all(feature = "float", feature = "array"))]
+fn tree_weight(node: &Dynamic) -> Result<FLOAT, Box<EvalAltResult>> {
+    if let Some(map) = node.read_lock::<rhai::Map>() {
+        if map.is_empty() {
+            return Err(EvalAltResult::ErrorArithmetic(
+                "Tree levels must not be empty".to_string(),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        map.values().map(tree_weight).sum()
+    } else {
+        crate::weights::to_weight(node)
+    }
+}
+
+/// A sampler for the empirical distribution of a set of observed values.
+#[cfg(all(feature = "float", feature = "array"))]
+#[derive(Debug, Clone)]
+pub struct EmpiricalSampler {
+    /// Observed values, sorted in ascending order.
+    sorted: Vec<FLOAT>,
+}
+
+/// A weighted sampler over indices, whose weights can be updated in place.
+///
+/// Weights are stored in a Fenwick tree, so that both updating a weight and drawing an index
+/// take `O(log n)` time. Weights are validated with `weights::to_weight` but not normalized,
+/// since normalizing would rescale every weight on each update.
+#[cfg(all(feature = "float", feature = "array"))]
+#[derive(Debug, Clone)]
+pub struct WeightedSampler {
+    /// Current weight of each index.
+    weights: Vec<FLOAT>,
+    /// Fenwick tree of partial sums of the weights, indexed from `1`.
+    tree: Vec<FLOAT>,
+}
+
+/// A drawer of the elements of an array in random order, without replacement.
+///
+/// Elements are drawn by a lazy Fisher-Yates shuffle, so each draw takes `O(1)` time and no
+/// upfront shuffling is needed.
+#[cfg(feature = "array")]
+#[derive(Debug, Clone)]
+pub struct ArrayDrawer {
+    /// Elements not yet drawn.
+    remaining: rhai::Array,
+}
+
+/// A shuffler with its own seeded random number generator, which advances with each shuffle.
+#[cfg(feature = "array")]
+#[derive(Debug, Clone)]
+pub struct Shuffler {
+    /// Generator used for shuffling, independent of the package's generator.
+    rng: rand::rngs::StdRng,
+}
+
+#[cfg(all(feature = "float", feature = "array"))]
+impl WeightedSampler {
+    fn new(weights: Vec<FLOAT>) -> Self {
+        let mut tree = vec![0.0; weights.len() + 1];
+
+        for i in 1..tree.len() {
+            tree[i] += weights[i - 1];
+
+            let parent = i + (i & i.wrapping_neg());
+
+            if parent < tree.len() {
+                tree[parent] += tree[i];
+            }
+        }
+
+        Self { weights, tree }
+    }
+
+    /// Set the weight of an index.
+    fn set(&mut self, index: usize, weight: FLOAT) {
+        let delta = weight - self.weights[index];
+        self.weights[index] = weight;
+
+        let mut i = index + 1;
+
+        while i < self.tree.len() {
+            self.tree[i] += delta;
+            i += i & i.wrapping_neg();
+        }
+    }
+
+    /// Total of all weights.
+    fn total(&self) -> FLOAT {
+        let mut i = self.weights.len();
+        let mut total = 0.0;
+
+        while i > 0 {
+            total += self.tree[i];
+            i &= i - 1;
+        }
+
+        total
+    }
+
+    /// Find the index whose range of cumulative weights contains `target`.
+    fn find(&self, mut target: FLOAT) -> usize {
+        let mut position = 0;
+        let mut step = self.weights.len().checked_next_power_of_two().unwrap_or(0);
+
+        while step > 0 {
+            if position + step < self.tree.len() && self.tree[position + step] <= target {
+                position += step;
+                target -= self.tree[position];
+            }
+            step >>= 1;
+        }
+
+        position.min(self.weights.len() - 1)
+    }
+}
+
+/// Check that a probability is between `0.0` and `1.0` (inclusive).
+#[cfg(feature = "float")]
+fn check_probability(probability: FLOAT) -> Result<(), Box<EvalAltResult>> {
+    if (0.0..=1.0).contains(&probability) {
+        Ok(())
+    } else {
+        Err(EvalAltResult::ErrorArithmetic(
+            format!(
+                "Invalid probability (must be between 0.0 and 1.0): {}",
+                probability
+            ),
+            Position::NONE,
+        )
+        .into())
+    }
+}
+
+/// Check the number of dice and sides for `dice_sum`, `dice` and `dice_roll`, including that
+/// the number of dice is within [`MAX_LENGTH`] and the maximum possible sum does not overflow.
+fn check_dice(count: rhai::INT, sides: rhai::INT) -> Result<(), Box<EvalAltResult>> {
+    if count < 0 {
+        return Err(EvalAltResult::ErrorArithmetic(
+            format!("Invalid number of dice (must not be negative): {}", count),
+            Position::NONE,
+        )
+        .into());
+    }
+    if count > MAX_LENGTH {
+        return Err(EvalAltResult::ErrorArithmetic(
+            format!("Too many dice (maximum {}): {}", MAX_LENGTH, count),
+            Position::NONE,
+        )
+        .into());
+    }
+    if sides < 1 {
+        return Err(EvalAltResult::ErrorArithmetic(
+            format!("Invalid number of sides (must be at least 1): {}", sides),
+            Position::NONE,
+        )
+        .into());
+    }
+    if count.checked_mul(sides).is_none() {
+        return Err(EvalAltResult::ErrorArithmetic(
+            format!("Dice sum overflows: {}d{}", count, sides),
+            Position::NONE,
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Check that the bounds of a floating-point range, and its width, are finite.
+///
+/// Uniform sampling panics on ranges that are not finite.
+#[cfg(feature = "float")]
+fn check_float_range(start: FLOAT, end: FLOAT) -> Result<(), Box<EvalAltResult>> {
+    if start.is_finite() && end.is_finite() && (end - start).is_finite() {
+        Ok(())
+    } else {
+        Err(EvalAltResult::ErrorArithmetic(
+            format!("Range is not finite: {}..{}", start, end),
+            Position::NONE,
+        )
+        .into())
+    }
+}
+
+/// Wrap an angle (in radians) into the range `(-π, π]`.
+#[cfg(feature = "float")]
+fn normalize_angle(angle: FLOAT) -> FLOAT {
+    let angle = (angle + PI).rem_euclid(2.0 * PI) - PI;
+
+    if angle <= -PI {
+        PI
+    } else {
+        angle
+    }
+}
+
+/// Cumulative distribution function of the standard normal distribution.
+///
+/// Uses a Chebyshev approximation of the complementary error function, which keeps a small
+/// _relative_ error even far into the lower tail.
+#[cfg(all(feature = "float", feature = "array"))]
+fn normal_cdf(x: FLOAT) -> FLOAT {
+    let z = (x / std::f64::consts::SQRT_2 as FLOAT).abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let poly = -z * z - 1.265_512_2
+        + t * (1.000_023_7
+            + t * (0.374_091_96
+                + t * (0.096_784_18
+                    + t * (-0.186_288_06
+                        + t * (0.278_868_07
+                            + t * (-1.135_203_98
+                                + t * (1.488_515_87 + t * (-0.822_152_23 + t * 0.170_872_77))))))));
+    let erfc = t * poly.exp();
+
+    if x < 0.0 {
+        0.5 * erfc
+    } else {
+        1.0 - 0.5 * erfc
+    }
+}
+
+/// Inverse of the cumulative distribution function of the standard normal distribution,
+/// using Acklam's rational approximation.
+#[cfg(all(feature = "float", feature = "array"))]
+fn normal_quantile(p: FLOAT) -> FLOAT {
+    const A: [FLOAT; 6] = [
+        -3.969_683_028_665_376e1,
+        2.209_460_984_245_205e2,
+        -2.759_285_104_469_687e2,
+        1.383_577_518_672_69e2,
+        -3.066_479_806_614_716e1,
+        2.506_628_277_459_239,
+    ];
+    const B: [FLOAT; 5] = [
+        -5.447_609_879_822_406e1,
+        1.615_858_368_580_409e2,
+        -1.556_989_798_598_866e2,
+        6.680_131_188_771_972e1,
+        -1.328_068_155_288_572e1,
+    ];
+    const C: [FLOAT; 6] = [
+        -7.784_894_002_430_293e-3,
+        -3.223_964_580_411_365e-1,
+        -2.400_758_277_161_838,
+        -2.549_732_539_343_734,
+        4.374_664_141_464_968,
+        2.938_163_982_698_783,
+    ];
+    const D: [FLOAT; 4] = [
+        7.784_695_709_041_462e-3,
+        3.224_671_290_700_398e-1,
+        2.445_134_137_142_996,
+        3.754_408_661_907_416,
+    ];
+    const P_LOW: FLOAT = 0.02425;
+
+    let tail = |q: FLOAT| {
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    };
+
+    if p <= 0.0 {
+        FLOAT::NEG_INFINITY
+    } else if p >= 1.0 {
+        FLOAT::INFINITY
+    } else if p < P_LOW {
+        tail((-2.0 * p.ln()).sqrt())
+    } else if p > 1.0 - P_LOW {
+        -tail((-2.0 * (1.0 - p).ln()).sqrt())
+    } else {
+        let q = p - 0.5;
+        let r = q * q;
+
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    }
+}
+
+/// Sample a normal distribution truncated to `[min, max]`, by inverting its cumulative
+/// distribution function.
+#[cfg(all(feature = "float", feature = "array"))]
+fn sample_truncated_normal(
+    rng: &mut impl rand::Rng,
+    mean: FLOAT,
+    std: FLOAT,
+    min: FLOAT,
+    max: FLOAT,
+) -> FLOAT {
+    let lower = (min - mean) / std;
+    let upper = (max - mean) / std;
+
+    // Work in the lower tail, where the cumulative distribution function is most precise
+    let (lower, upper, sign) = if lower > 0.0 {
+        (-upper, -lower, -1.0)
+    } else {
+        (lower, upper, 1.0)
+    };
+
+    let p_lower = normal_cdf(lower);
+    let p_upper = normal_cdf(upper);
+    let p = p_lower + rng.gen::<FLOAT>() * (p_upper - p_lower);
+
+    (mean + sign * std * normal_quantile(p)).clamp(min, max)
+}
+
+/// Compute the lower-triangular Cholesky factor `L` of a symmetric positive semi-definite
+/// matrix, such that `L * Lᵀ` equals the matrix.
+///
+/// Pivots that are not positive due to rounding errors are treated as zero, so that
+/// (numerically) singular matrices still produce a usable factor.
+#[cfg(all(feature = "float", feature = "array"))]
+fn cholesky(matrix: &[Vec<FLOAT>]) -> Vec<Vec<FLOAT>> {
+    let n = matrix.len();
+    let mut lower = vec![vec![0.0; n]; n];
+
+    for j in 0..n {
+        let sum: FLOAT = lower[j][..j].iter().map(|v| v * v).sum();
+        let pivot = (matrix[j][j] - sum).max(0.0).sqrt();
+
+        lower[j][j] = pivot;
+
+        if pivot > 0.0 {
+            for i in j + 1..n {
+                let sum: FLOAT = (0..j).map(|k| lower[i][k] * lower[j][k]).sum();
+                lower[i][j] = (matrix[i][j] - sum) / pivot;
+            }
+        }
+    }
+
+    lower
+}
+
+thread_local! {
+    /// Number of attempts and accepted samples of the most recent call to a rejection-based function.
+    static REJECTION_STATS: std::cell::Cell<(usize, usize)> = const { std::cell::Cell::new((0, 0)) };
+}
+
+/// Record the number of attempts and accepted samples of a call to a rejection-based function.
+fn record_rejection_stats(attempts: usize, accepted: usize) {
+    REJECTION_STATS.with(|stats| stats.set((attempts, accepted)));
+}
+
 #[export_module]
 pub mod rand_functions {
     use rand::prelude::*;
-    use rhai::{EvalAltResult, Position, INT};
+    use rhai::{EvalAltResult, FnPtr, Position, INT};
     use std::ops::{Range, RangeInclusive};
 
     #[cfg(feature = "float")]
     use rhai::FLOAT;
 
+    #[cfg(feature = "float")]
+    use super::PI;
+
+    #[cfg(feature = "float")]
+    use rand_distr::StandardNormal;
+
+    #[cfg(all(feature = "float", feature = "array"))]
+    use rand_distr::Exp1;
+
+    #[cfg(feature = "array")]
+    use rhai::Array;
+
+    #[cfg(feature = "array")]
+    use super::{ArrayDrawer, Shuffler};
+
+    #[cfg(all(feature = "float", feature = "array"))]
+    use super::{EmpiricalSampler, WeightedSampler};
+
     #[cfg(feature = "decimal")]
     use rust_decimal::Decimal;
 
+    /// Seed the random number generator, so that the same sequence of random values is
+    /// generated every time after calling this function with the same `seed`.
+    ///
+    /// This affects all random functions of the package for the rest of the run, but not other
+    /// instances of the package.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// rand_seed(42);
+    ///
+    /// let x = rand();
+    ///
+    /// rand_seed(42);
+    ///
+    /// print(rand() == x);     // prints true
+    /// ```
+    #[rhai_fn(volatile)]
+    pub fn rand_seed(ctx: NativeCallContext, seed: INT) {
+        crate::rng::seed(&ctx, seed as u64);
+    }
+
+    /// Remove the seed set by `rand_seed` (or `RandomPackage::with_seed`), so that random values
+    /// are generated non-deterministically again.
+    ///
+    /// This affects all random functions of the package for the rest of the run, but not other
+    /// instances of the package.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// rand_seed(42);
+    ///
+    /// let x = rand();     // always the same value
+    ///
+    /// rand_clear_seed();
+    ///
+    /// let y = rand();     // a different value on every run
+    /// ```
+    #[rhai_fn(volatile)]
+    pub fn rand_clear_seed(ctx: NativeCallContext) {
+        crate::rng::clear_seed(&ctx);
+    }
+
     /// Generate a random boolean value.
     ///
     /// ### Example
@@ -24,8 +473,9 @@ pub mod rand_functions {
     ///     print("You hit the Jackpot!")
     /// }
     /// ```
-    pub fn rand_bool() -> bool {
-        rand::random()
+    #[rhai_fn(volatile)]
+    pub fn rand_bool(ctx: NativeCallContext) -> bool {
+        crate::rng::rng(&ctx).gen()
     }
 
     /// Generate a random boolean value with a probability of being `true`.
@@ -43,20 +493,15 @@ pub mod rand_functions {
     /// }
     /// ```
     #[cfg(feature = "float")]
-    #[rhai_fn(name = "rand_bool", return_raw)]
-    pub fn rand_bool_with_probability(probability: FLOAT) -> Result<bool, Box<EvalAltResult>> {
-        if probability < 0.0 || probability > 1.0 {
-            Err(EvalAltResult::ErrorArithmetic(
-                format!(
-                    "Invalid probability (must be between 0.0 and 1.0): {}",
-                    probability
-                ),
-                Position::NONE,
-            )
-            .into())
-        } else {
-            Ok(rand::thread_rng().gen_bool(probability as f64))
-        }
+    #[rhai_fn(name = "rand_bool", return_raw, volatile)]
+    #[allow(clippy::unnecessary_cast)]
+    pub fn rand_bool_with_probability(
+        ctx: NativeCallContext,
+        probability: FLOAT,
+    ) -> Result<bool, Box<EvalAltResult>> {
+        super::check_probability(probability)?;
+
+        Ok(crate::rng::rng(&ctx).gen_bool(probability as f64))
     }
 
     /// Generate a random integer number.
@@ -68,12 +513,33 @@ pub mod rand_functions {
     ///
     /// print(`I'll give you a random number: ${number}`);
     /// ```
-    pub fn rand() -> INT {
-        rand::random()
+    #[rhai_fn(volatile)]
+    pub fn rand(ctx: NativeCallContext) -> INT {
+        crate::rng::rng(&ctx).gen()
+    }
+
+    /// Generate a random sign, i.e. either `-1` or `1` with equal probability.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let direction = rand_sign();
+    ///
+    /// print(`Moving ${if direction > 0 { "right" } else { "left" }}`);
+    /// ```
+    #[rhai_fn(volatile)]
+    pub fn rand_sign(ctx: NativeCallContext) -> INT {
+        if crate::rng::rng(&ctx).gen() {
+            1
+        } else {
+            -1
+        }
     }
 
     /// Generate a random integer number within an exclusive range.
     ///
+    /// `range` must not be empty, i.e. its start must be less than its end.
+    ///
     /// ### Example
     ///
     /// ```rhai
@@ -81,8 +547,11 @@ pub mod rand_functions {
     ///
     /// print(`I'll give you a random number between 18 and 38: ${number}`);
     /// ```
-    #[rhai_fn(name = "rand", return_raw)]
-    pub fn rand_exclusive_range(range: Range<INT>) -> Result<INT, Box<EvalAltResult>> {
+    #[rhai_fn(name = "rand", return_raw, volatile)]
+    pub fn rand_exclusive_range(
+        ctx: NativeCallContext,
+        range: Range<INT>,
+    ) -> Result<INT, Box<EvalAltResult>> {
         if range.is_empty() {
             Err(EvalAltResult::ErrorArithmetic(
                 format!("Range is empty: {:?}", range),
@@ -90,12 +559,14 @@ pub mod rand_functions {
             )
             .into())
         } else {
-            Ok(rand::thread_rng().gen_range(range))
+            Ok(crate::rng::rng(&ctx).gen_range(range))
         }
     }
 
     /// Generate a random integer number within an inclusive range.
     ///
+    /// `range` must not be empty, i.e. its start must not be greater than its end.
+    ///
     /// ### Example
     ///
     /// ```rhai
@@ -103,8 +574,11 @@ pub mod rand_functions {
     ///
     /// print(`I'll give you a random number between 18 and 38: ${number}`);
     /// ```
-    #[rhai_fn(name = "rand", return_raw)]
-    pub fn rand_inclusive_range(range: RangeInclusive<INT>) -> Result<INT, Box<EvalAltResult>> {
+    #[rhai_fn(name = "rand", return_raw, volatile)]
+    pub fn rand_inclusive_range(
+        ctx: NativeCallContext,
+        range: RangeInclusive<INT>,
+    ) -> Result<INT, Box<EvalAltResult>> {
         if range.is_empty() {
             Err(EvalAltResult::ErrorArithmetic(
                 format!("Range is empty: {:?}", range),
@@ -112,12 +586,15 @@ pub mod rand_functions {
             )
             .into())
         } else {
-            Ok(rand::thread_rng().gen_range(range))
+            Ok(crate::rng::rng(&ctx).gen_range(range))
         }
     }
 
     /// Generate a random integer number within an inclusive range.
     ///
+    /// If `start` equals `end`, that value is always returned. `start` must not be greater than
+    /// `end`.
+    ///
     /// ### Example
     ///
     /// ```rhai
@@ -125,17 +602,147 @@ pub mod rand_functions {
     ///
     /// print(`I'll give you a random number between 18 and 38: ${number}`);
     /// ```
-    #[rhai_fn(name = "rand", return_raw)]
-    pub fn rand_from_to_inclusive(start: INT, end: INT) -> Result<INT, Box<EvalAltResult>> {
-        if start >= end {
+    #[rhai_fn(name = "rand", return_raw, volatile)]
+    pub fn rand_from_to_inclusive(
+        ctx: NativeCallContext,
+        start: INT,
+        end: INT,
+    ) -> Result<INT, Box<EvalAltResult>> {
+        if start > end {
             Err(EvalAltResult::ErrorArithmetic(
-                format!("Range is empty: {}..{}", start, end),
+                format!("Range is empty: {}..={}", start, end),
                 Position::NONE,
             )
             .into())
         } else {
-            Ok(rand::thread_rng().gen_range(start..=end))
+            // Inclusive ranges never overflow, even spanning the full `INT::MIN..=INT::MAX`
+            Ok(crate::rng::rng(&ctx).gen_range(start..=end))
+        }
+    }
+
+    /// Generate a random printable ASCII character, between `'!'` and `'~'` (inclusive).
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let ch = rand_char();
+    ///
+    /// print(`I'll give you a random character: ${ch}`);
+    /// ```
+    #[rhai_fn(volatile)]
+    pub fn rand_char(ctx: NativeCallContext) -> char {
+        crate::rng::rng(&ctx).gen_range('!'..='~')
+    }
+
+    /// Generate a random character between `from` and `to` (inclusive), by Unicode code point.
+    ///
+    /// Surrogate code points (which are not valid characters) are skipped when the range
+    /// spans them.
+    ///
+    /// `from` must not be greater than `to`.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let letter = rand_char('a', 'z');
+    ///
+    /// print(`I'll give you a random lowercase letter: ${letter}`);
+    /// ```
+    #[rhai_fn(name = "rand_char", return_raw, volatile)]
+    pub fn rand_char_range(
+        ctx: NativeCallContext,
+        from: char,
+        to: char,
+    ) -> Result<char, Box<EvalAltResult>> {
+        if from > to {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Range is empty: {:?}..={:?}", from, to),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        const SURROGATES: std::ops::RangeInclusive<u32> = 0xD800..=0xDFFF;
+
+        let (from, to) = (from as u32, to as u32);
+        let gap = if from < *SURROGATES.start() && to > *SURROGATES.end() {
+            SURROGATES.end() - SURROGATES.start() + 1
+        } else {
+            0
+        };
+
+        let mut code = crate::rng::rng(&ctx).gen_range(from..=to - gap);
+
+        if gap > 0 && code >= *SURROGATES.start() {
+            code += gap;
+        }
+
+        Ok(char::from_u32(code).expect("surrogates are skipped"))
+    }
+
+    /// Generate a random string of `len` alphanumeric characters (`A-Z`, `a-z` and `0-9`).
+    ///
+    /// An empty string is returned if `len` is zero or negative.
+    /// `len` must not exceed 1,048,576.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let id = rand_string(8);
+    ///
+    /// print(`I'll give you a random identifier: ${id}`);
+    /// ```
+    #[rhai_fn(return_raw, volatile)]
+    pub fn rand_string(ctx: NativeCallContext, len: INT) -> Result<String, Box<EvalAltResult>> {
+        if len > super::MAX_LENGTH {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "String length is too large (maximum {}): {}",
+                    super::MAX_LENGTH,
+                    len
+                ),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        Ok(crate::rng::rng(&ctx)
+            .sample_iter(rand::distributions::Alphanumeric)
+            .take(len.max(0) as usize)
+            .map(char::from)
+            .collect())
+    }
+
+    /// Generate a BLOB of `len` random bytes.
+    ///
+    /// An empty BLOB is returned if `len` is zero or negative.
+    /// `len` must not exceed 1,048,576.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let salt = rand_bytes(16);
+    ///
+    /// print(`I'll give you a random salt: ${salt}`);
+    /// ```
+    #[rhai_fn(return_raw, volatile)]
+    pub fn rand_bytes(ctx: NativeCallContext, len: INT) -> Result<rhai::Blob, Box<EvalAltResult>> {
+        if len > super::MAX_LENGTH {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "BLOB length is too large (maximum {}): {}",
+                    super::MAX_LENGTH,
+                    len
+                ),
+                Position::NONE,
+            )
+            .into());
         }
+
+        let mut blob = vec![0; len.max(0) as usize];
+        crate::rng::rng(&ctx).fill_bytes(&mut blob);
+
+        Ok(blob)
     }
 
     /// Generate a random floating-point number between `0.0` and `1.0` (exclusive).
@@ -151,12 +758,23 @@ pub mod rand_functions {
     /// print(`I'll give you a random number between 0 and 1: ${number}`);
     /// ```
     #[cfg(feature = "float")]
-    pub fn rand_float() -> FLOAT {
-        rand::random()
+    #[rhai_fn(volatile)]
+    pub fn rand_float(ctx: NativeCallContext) -> FLOAT {
+        crate::rng::rng(&ctx).gen()
     }
-    /// Generate a random floating-point number within an exclusive range.
+
+    /// Generate a random floating-point number within an exclusive range, i.e. between `start`
+    /// (inclusive) and `end` (exclusive).
     /// Requires the `float` feature.
     ///
+    /// `end` is _excluded_ from the possibilities, just like the integer range `start..end`.
+    /// Use `rand_float_inclusive` to include it.
+    ///
+    /// Rhai ranges can only hold integers, so floating-point ranges such as `0.0..1.0` cannot be
+    /// passed to this function; pass the two bounds separately instead.
+    ///
+    /// `start` must be less than `end`, and both must be finite.
+    ///
     /// ### Example
     ///
     /// ```rhai
@@ -165,54 +783,3723 @@ pub mod rand_functions {
     /// print(`I'll give you a random number between 123.456 and 789.678: ${number}`);
     /// ```
     #[cfg(feature = "float")]
-    #[rhai_fn(name = "rand_float", return_raw)]
-    pub fn rand_float_range(start: FLOAT, end: FLOAT) -> Result<FLOAT, Box<EvalAltResult>> {
+    #[rhai_fn(name = "rand_float", return_raw, volatile)]
+    pub fn rand_float_range(
+        ctx: NativeCallContext,
+        start: FLOAT,
+        end: FLOAT,
+    ) -> Result<FLOAT, Box<EvalAltResult>> {
+        super::check_float_range(start, end)?;
+
         if start >= end {
-            Err(EvalAltResult::ErrorArithmetic(
+            return Err(EvalAltResult::ErrorArithmetic(
                 format!("Range is empty: {}..{}", start, end),
                 Position::NONE,
             )
-            .into())
-        } else {
-            Ok(rand::thread_rng().gen_range(start..=end))
+            .into());
         }
+
+        Ok(crate::rng::rng(&ctx).gen_range(start..end))
     }
 
-    /// Generate a random [decimal](https://crates.io/crates/rust_decimal) number.
-    /// Requires the `decimal` feature.
+    /// Generate a random floating-point number within an inclusive range, i.e. between `start`
+    /// and `end` (both inclusive).
+    /// Requires the `float` feature.
+    ///
+    /// If `start` equals `end`, it is always returned.
+    ///
+    /// `start` must not be greater than `end`, and both must be finite.
     ///
     /// ### Example
     ///
     /// ```rhai
-    /// let number = rand_decimal();
+    /// let number = rand_float_inclusive(0.0, 1.0);
     ///
-    /// print(`I'll give you a random decimal number: ${number}`);
+    /// print(`I'll give you a random number between 0 and 1 (inclusive): ${number}`);
     /// ```
-    #[cfg(feature = "decimal")]
-    pub fn rand_decimal() -> Decimal {
-        rand::random()
+    #[cfg(feature = "float")]
+    #[rhai_fn(return_raw, volatile)]
+    pub fn rand_float_inclusive(
+        ctx: NativeCallContext,
+        start: FLOAT,
+        end: FLOAT,
+    ) -> Result<FLOAT, Box<EvalAltResult>> {
+        super::check_float_range(start, end)?;
+
+        if start > end {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Range is empty: {}..={}", start, end),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        Ok(crate::rng::rng(&ctx).gen_range(start..=end))
     }
-    /// Generate a random [decimal](https://crates.io/crates/rust_decimal) number within a range.
-    /// Requires the `decimal` feature.
+
+    /// Generate a deterministic random floating-point number within an exclusive range, from a
+    /// generator seeded with `seed`.
+    /// Requires the `float` feature.
+    ///
+    /// The same `seed` and range always produce the same number. This does not affect the
+    /// sequence of values generated by other functions, even after calling `rand_seed`.
     ///
     /// ### Example
     ///
     /// ```rhai
-    /// let number = rand(18.to_decimal(), 38.to_decimal());
+    /// let x = rand_float_seeded(42, 0.0, 100.0);
     ///
-    /// print(`I'll give you a random number between 18 and 38: ${number}`);
+    /// print(rand_float_seeded(42, 0.0, 100.0) == x);     // prints true
     /// ```
-    #[cfg(feature = "decimal")]
-    #[rhai_fn(name = "rand_decimal", return_raw)]
-    pub fn rand_decimal_range(start: Decimal, end: Decimal) -> Result<Decimal, Box<EvalAltResult>> {
+    #[cfg(feature = "float")]
+    #[rhai_fn(return_raw)]
+    pub fn rand_float_seeded(
+        seed: INT,
+        start: FLOAT,
+        end: FLOAT,
+    ) -> Result<FLOAT, Box<EvalAltResult>> {
+        super::check_float_range(start, end)?;
+
         if start >= end {
-            Err(EvalAltResult::ErrorArithmetic(
+            return Err(EvalAltResult::ErrorArithmetic(
                 format!("Range is empty: {}..{}", start, end),
                 Position::NONE,
             )
-            .into())
-        } else {
-            Ok(rand::thread_rng().gen_range(start..=end))
+            .into());
         }
+
+        Ok(rand::rngs::StdRng::seed_from_u64(seed as u64).gen_range(start..end))
+    }
+
+    /// Generate a random floating-point number between `0.0` and `1.0` following a triangular
+    /// distribution that peaks at `0.5`, computed as the average of two uniform random numbers.
+    /// Requires the `float` feature.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let number = rand_triangular_unit();
+    ///
+    /// print(`I'll give you a random number, most likely near 0.5: ${number}`);
+    /// ```
+    #[cfg(feature = "float")]
+    #[rhai_fn(volatile)]
+    pub fn rand_triangular_unit(ctx: NativeCallContext) -> FLOAT {
+        let mut rng = crate::rng::rng(&ctx);
+        let (u1, u2): (FLOAT, FLOAT) = (rng.gen(), rng.gen());
+
+        (u1 + u2) / 2.0
+    }
+
+    /// Generate a random floating-point number following a normal (Gaussian) distribution with
+    /// the specified `mean` and standard deviation `std_dev`.
+    /// Requires the `float` feature.
+    ///
+    /// A `std_dev` of `0.0` always returns `mean`.
+    ///
+    /// `std_dev` must not be negative.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let height = rand_normal(170.0, 10.0);
+    ///
+    /// print(`A random height in centimeters: ${height}`);
+    /// ```
+    #[cfg(feature = "float")]
+    #[rhai_fn(return_raw, volatile)]
+    pub fn rand_normal(
+        ctx: NativeCallContext,
+        mean: FLOAT,
+        std_dev: FLOAT,
+    ) -> Result<FLOAT, Box<EvalAltResult>> {
+        // `Normal::new` accepts a negative standard deviation, so it is checked separately
+        if std_dev < 0.0 {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "Invalid standard deviation (must not be negative): {}",
+                    std_dev
+                ),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        let normal = rand_distr::Normal::new(mean, std_dev).map_err(|err| {
+            EvalAltResult::ErrorArithmetic(
+                format!("Invalid normal distribution parameters: {}", err),
+                Position::NONE,
+            )
+        })?;
+
+        Ok(crate::rng::rng(&ctx).sample(normal))
+    }
+
+    /// Generate a random floating-point number following an exponential distribution with the
+    /// specified rate `lambda`, e.g. the time between events that occur `lambda` times per unit
+    /// of time on average.
+    /// Requires the `float` feature.
+    ///
+    /// The result is never negative, and its mean is `1 / lambda`.
+    ///
+    /// `lambda` must be positive.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let wait = rand_exp(0.5);      // customers arrive every 2 minutes on average
+    ///
+    /// print(`Next customer arrives in ${wait} minutes`);
+    /// ```
+    #[cfg(feature = "float")]
+    #[rhai_fn(return_raw, volatile)]
+    pub fn rand_exp(ctx: NativeCallContext, lambda: FLOAT) -> Result<FLOAT, Box<EvalAltResult>> {
+        if !(lambda > 0.0 && lambda.is_finite()) {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Invalid rate (must be positive): {}", lambda),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        let exp = rand_distr::Exp::new(lambda).map_err(|err| {
+            EvalAltResult::ErrorArithmetic(
+                format!("Invalid exponential distribution parameters: {}", err),
+                Position::NONE,
+            )
+        })?;
+
+        Ok(crate::rng::rng(&ctx).sample(exp))
+    }
+
+    /// Generate a random integer number following a binomial distribution, i.e. the number of
+    /// successes out of `trials` independent trials that each succeed with probability `p`.
+    /// Requires the `float` feature.
+    ///
+    /// The result is between `0` and `trials` (inclusive), and its mean is `trials * p`.
+    ///
+    /// `trials` must not be negative, and `p` must be between `0.0` and `1.0` (inclusive).
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let hits = rand_binomial(20, 0.3);
+    ///
+    /// print(`You hit the target ${hits} times out of 20`);
+    /// ```
+    #[cfg(feature = "float")]
+    #[rhai_fn(return_raw, volatile)]
+    #[allow(clippy::unnecessary_cast)]
+    pub fn rand_binomial(
+        ctx: NativeCallContext,
+        trials: INT,
+        p: FLOAT,
+    ) -> Result<INT, Box<EvalAltResult>> {
+        if trials < 0 {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "Invalid number of trials (must not be negative): {}",
+                    trials
+                ),
+                Position::NONE,
+            )
+            .into());
+        }
+        super::check_probability(p)?;
+
+        let binomial = rand_distr::Binomial::new(trials as u64, p as f64).map_err(|err| {
+            EvalAltResult::ErrorArithmetic(
+                format!("Invalid binomial distribution parameters: {}", err),
+                Position::NONE,
+            )
+        })?;
+
+        Ok(crate::rng::rng(&ctx).sample(binomial) as INT)
+    }
+
+    /// Generate a random [decimal](https://crates.io/crates/rust_decimal) number.
+    /// Requires the `decimal` feature.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let number = rand_decimal();
+    ///
+    /// print(`I'll give you a random decimal number: ${number}`);
+    /// ```
+    #[cfg(feature = "decimal")]
+    #[rhai_fn(volatile)]
+    pub fn rand_decimal(ctx: NativeCallContext) -> Decimal {
+        crate::rng::rng(&ctx).gen()
+    }
+    /// Generate a random [decimal](https://crates.io/crates/rust_decimal) number within a range.
+    /// Requires the `decimal` feature.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let number = rand(18.to_decimal(), 38.to_decimal());
+    ///
+    /// print(`I'll give you a random number between 18 and 38: ${number}`);
+    /// ```
+    #[cfg(feature = "decimal")]
+    #[rhai_fn(name = "rand_decimal", return_raw, volatile)]
+    pub fn rand_decimal_range(
+        ctx: NativeCallContext,
+        start: Decimal,
+        end: Decimal,
+    ) -> Result<Decimal, Box<EvalAltResult>> {
+        if start >= end {
+            Err(EvalAltResult::ErrorArithmetic(
+                format!("Range is empty: {}..{}", start, end),
+                Position::NONE,
+            )
+            .into())
+        } else {
+            Ok(crate::rng::rng(&ctx).gen_range(start..=end))
+        }
+    }
+
+    /// Generate a random angle (in radians) from the von Mises distribution, i.e. the
+    /// circular analogue of the normal distribution, centered on `mean_angle`.
+    /// Requires the `float` feature.
+    ///
+    /// `kappa` is the concentration, which must not be negative.
+    /// Higher values cluster the angles more tightly around `mean_angle`,
+    /// while `0.0` gives uniformly-distributed angles.
+    ///
+    /// The result is always in the range `(-π, π]`.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let angle = rand_von_mises(0.0, 4.0);
+    ///
+    /// print(`I'll give you a random direction close to 0: ${angle}`);
+    /// ```
+    #[cfg(feature = "float")]
+    #[rhai_fn(return_raw, volatile)]
+    pub fn rand_von_mises(
+        ctx: NativeCallContext,
+        mean_angle: FLOAT,
+        kappa: FLOAT,
+    ) -> Result<FLOAT, Box<EvalAltResult>> {
+        if kappa < 0.0 || !kappa.is_finite() {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Invalid concentration (must be non-negative): {}", kappa),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        let mut rng = crate::rng::rng(&ctx);
+
+        if kappa < 1e-6 {
+            super::record_rejection_stats(1, 1);
+            return Ok(super::normalize_angle(rng.gen_range(-PI..PI) + mean_angle));
+        }
+
+        // Best & Fisher (1979) rejection algorithm.
+        let tau = 1.0 + (1.0 + 4.0 * kappa * kappa).sqrt();
+        let rho = (tau - (2.0 * tau).sqrt()) / (2.0 * kappa);
+        let r = (1.0 + rho * rho) / (2.0 * rho);
+
+        let mut attempts = 0;
+
+        let f = loop {
+            attempts += 1;
+
+            let z = (PI * rng.gen::<FLOAT>()).cos();
+            let f = (1.0 + r * z) / (r + z);
+            let c = kappa * (r - f);
+            let u: FLOAT = rng.gen();
+
+            if c * (2.0 - c) > u || (c / u).ln() + 1.0 >= c {
+                break f;
+            }
+        };
+
+        super::record_rejection_stats(attempts, 1);
+
+        let theta = if rng.gen::<bool>() {
+            f.acos()
+        } else {
+            -f.acos()
+        };
+
+        Ok(super::normalize_angle(mean_angle + theta))
+    }
+
+    /// Generate a random angle (in radians) uniformly distributed within an arc of `width`
+    /// radians centered on `center`, i.e. between `center - width / 2` and `center + width / 2`.
+    /// Requires the `float` feature.
+    ///
+    /// The result is always in the range `(-π, π]`, wrapping around if the arc crosses `π`.
+    ///
+    /// `width` must not be negative.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let aim = 0.5;
+    ///
+    /// for pellet in 0..8 {
+    ///     let angle = rand_arc_angle(aim, 0.3);     // shotgun spread
+    ///
+    ///     print(`Pellet ${pellet} flies at ${angle}`);
+    /// }
+    /// ```
+    #[cfg(feature = "float")]
+    #[rhai_fn(return_raw, volatile)]
+    pub fn rand_arc_angle(
+        ctx: NativeCallContext,
+        center: FLOAT,
+        width: FLOAT,
+    ) -> Result<FLOAT, Box<EvalAltResult>> {
+        if width < 0.0 || !width.is_finite() {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Invalid arc width (must not be negative): {}", width),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        let offset = crate::rng::rng(&ctx).gen_range(-0.5..=0.5) * width;
+
+        Ok(super::normalize_angle(center + offset))
+    }
+
+    /// Generate the next state of a two-state Markov chain of boolean values.
+    /// Requires the `float` feature.
+    ///
+    /// If `prev` is `true`, the result stays `true` with probability `p_stay_true`;
+    /// otherwise it stays `false` with probability `p_stay_false`.
+    /// Both probabilities must be between `0.0` and `1.0` (inclusive).
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let raining = false;
+    ///
+    /// for day in 0..7 {
+    ///     raining = markov_bool(raining, 0.7, 0.9);
+    ///
+    ///     print(`Day ${day}: ${if raining { "rain" } else { "sun" }}`);
+    /// }
+    /// ```
+    #[cfg(feature = "float")]
+    #[rhai_fn(return_raw, volatile)]
+    #[allow(clippy::unnecessary_cast)]
+    pub fn markov_bool(
+        ctx: NativeCallContext,
+        prev: bool,
+        p_stay_true: FLOAT,
+        p_stay_false: FLOAT,
+    ) -> Result<bool, Box<EvalAltResult>> {
+        super::check_probability(p_stay_true)?;
+        super::check_probability(p_stay_false)?;
+
+        let mut rng = crate::rng::rng(&ctx);
+
+        if prev {
+            Ok(rng.gen_bool(p_stay_true as f64))
+        } else {
+            Ok(!rng.gen_bool(p_stay_false as f64))
+        }
+    }
+
+    /// Generate a random boolean value that is `true` with probability `p`, except during a
+    /// cooldown window after the last success, when it is always `false`.
+    /// Requires the `float` feature.
+    ///
+    /// The result is always `false` if `steps_since_success` is less than `cooldown`.
+    ///
+    /// `p` must be between `0.0` and `1.0` (inclusive), and `steps_since_success` and `cooldown`
+    /// must not be negative.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let steps = 0;
+    ///
+    /// for turn in 0..20 {
+    ///     if cooldown_roll(0.5, steps, 3) {
+    ///         print(`Special attack on turn ${turn}!`);
+    ///         steps = 0;
+    ///     } else {
+    ///         steps += 1;
+    ///     }
+    /// }
+    /// ```
+    #[cfg(feature = "float")]
+    #[rhai_fn(return_raw, volatile)]
+    #[allow(clippy::unnecessary_cast)]
+    pub fn cooldown_roll(
+        ctx: NativeCallContext,
+        p: FLOAT,
+        steps_since_success: INT,
+        cooldown: INT,
+    ) -> Result<bool, Box<EvalAltResult>> {
+        super::check_probability(p)?;
+
+        if steps_since_success < 0 || cooldown < 0 {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "Invalid steps or cooldown (must not be negative): {}, {}",
+                    steps_since_success, cooldown
+                ),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        if steps_since_success < cooldown {
+            return Ok(false);
+        }
+
+        Ok(crate::rng::rng(&ctx).gen_bool(p as f64))
+    }
+
+    /// Generate a random point uniformly distributed inside a simple polygon.
+    /// Requires the `float` and `array` features.
+    ///
+    /// `vertices` must be an array of at least three `[x, y]` points with finite coordinates, in
+    /// order around the polygon.
+    /// The returned point is an `[x, y]` array.
+    ///
+    /// Points are sampled within the bounding box of the polygon until one falls inside it,
+    /// giving up with an error after a large number of attempts (e.g. for a degenerate polygon).
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let triangle = [[0, 0], [10, 0], [5, 8]];
+    ///
+    /// let point = rand_in_polygon(triangle);
+    ///
+    /// print(`I'll give you a random point inside the triangle: ${point}`);
+    /// ```
+    #[cfg(all(feature = "float", feature = "array"))]
+    #[rhai_fn(return_raw, volatile)]
+    pub fn rand_in_polygon(
+        ctx: NativeCallContext,
+        vertices: Array,
+    ) -> Result<Array, Box<EvalAltResult>> {
+        const MAX_TRIES: usize = 10_000;
+
+        let points = vertices
+            .iter()
+            .map(|v| {
+                super::to_vector(v, 2)
+                    .filter(|p| p.iter().all(|c| c.is_finite()))
+                    .map(|p| (p[0], p[1]))
+            })
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| {
+                EvalAltResult::ErrorArithmetic(
+                    "Polygon vertices must be [x, y] points with finite coordinates".to_string(),
+                    Position::NONE,
+                )
+            })?;
+
+        if points.len() < 3 {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "Polygon must have at least three vertices: {}",
+                    points.len()
+                ),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        let (mut min_x, mut min_y) = points[0];
+        let (mut max_x, mut max_y) = points[0];
+
+        for &(x, y) in &points {
+            min_x = min_x.min(x);
+            min_y = min_y.min(y);
+            max_x = max_x.max(x);
+            max_y = max_y.max(y);
+        }
+
+        if min_x >= max_x || min_y >= max_y {
+            return Err(EvalAltResult::ErrorArithmetic(
+                "Polygon is degenerate (has no area)".to_string(),
+                Position::NONE,
+            )
+            .into());
+        }
+        super::check_float_range(min_x, max_x)?;
+        super::check_float_range(min_y, max_y)?;
+
+        let mut rng = crate::rng::rng(&ctx);
+
+        for attempts in 1..=MAX_TRIES {
+            let x = rng.gen_range(min_x..max_x);
+            let y = rng.gen_range(min_y..max_y);
+
+            // Even-odd rule: count the edges crossed by a horizontal ray from the point.
+            let mut inside = false;
+
+            for (i, &(x1, y1)) in points.iter().enumerate() {
+                let (x2, y2) = points[(i + 1) % points.len()];
+
+                if (y1 > y) != (y2 > y) && x < (x2 - x1) * (y - y1) / (y2 - y1) + x1 {
+                    inside = !inside;
+                }
+            }
+
+            if inside {
+                super::record_rejection_stats(attempts, 1);
+                return Ok(vec![x.into(), y.into()]);
+            }
+        }
+
+        super::record_rejection_stats(MAX_TRIES, 0);
+
+        Err(EvalAltResult::ErrorArithmetic(
+            format!(
+                "Cannot find a point inside the polygon after {} attempts",
+                MAX_TRIES
+            ),
+            Position::NONE,
+        )
+        .into())
+    }
+
+    /// Roll a loaded die with `faces` sides, returning a face between `1` and `faces`
+    /// (inclusive) with probabilities proportional to `weights`.
+    /// Requires the `float` and `array` features.
+    ///
+    /// `weights` must contain exactly `faces` non-negative numbers, which must not all be zero.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// // Six comes up half of the time
+    /// let roll = loaded_die(6, [1, 1, 1, 1, 1, 5]);
+    ///
+    /// print(`You rolled a ${roll}`);
+    /// ```
+    #[cfg(all(feature = "float", feature = "array"))]
+    #[rhai_fn(return_raw, volatile)]
+    pub fn loaded_die(
+        ctx: NativeCallContext,
+        faces: INT,
+        weights: Array,
+    ) -> Result<INT, Box<EvalAltResult>> {
+        if faces < 1 {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Invalid number of faces (must be at least 1): {}", faces),
+                Position::NONE,
+            )
+            .into());
+        }
+        if weights.len() as INT != faces {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "Number of weights ({}) does not match the number of faces ({})",
+                    weights.len(),
+                    faces
+                ),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        let dist = crate::weights::from_array(&weights)?;
+
+        Ok(dist.sample(&mut crate::rng::rng(&ctx)) as INT + 1)
+    }
+
+    /// Generate a random floating-point number from a custom distribution, given its
+    /// quantile function (i.e. the inverse of its cumulative distribution function).
+    /// Requires the `float` feature.
+    ///
+    /// A random number `u` between `0.0` and `1.0` (exclusive) is passed to `quantile`,
+    /// and the result is returned. `quantile` must return a number.
+    ///
+    /// Calling back into a script function for every sample is much slower than the
+    /// built-in distributions, so prefer those when one fits.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// // Exponential distribution with rate 2
+    /// let number = sample_inverse(|u| -(1.0 - u).ln() / 2.0);
+    ///
+    /// print(`I'll give you a random waiting time: ${number}`);
+    /// ```
+    #[cfg(feature = "float")]
+    #[rhai_fn(return_raw, volatile)]
+    pub fn sample_inverse(
+        ctx: NativeCallContext,
+        quantile: FnPtr,
+    ) -> Result<FLOAT, Box<EvalAltResult>> {
+        let u: FLOAT = crate::rng::rng(&ctx).gen();
+        let value: Dynamic = quantile.call_within_context(&ctx, (u,))?;
+
+        super::to_float(&value).ok_or_else(|| {
+            EvalAltResult::ErrorMismatchOutputType(
+                "number".to_string(),
+                value.type_name().to_string(),
+                Position::NONE,
+            )
+            .into()
+        })
+    }
+
+    /// Make `n` random draws (with replacement) from categories with probabilities
+    /// proportional to `weights`, and return the number of times each category was drawn.
+    /// Requires the `float` and `array` features.
+    ///
+    /// This follows the multinomial distribution. The returned array has the same length as
+    /// `weights`, and its counts sum to `n`.
+    ///
+    /// `weights` must be non-negative numbers, which must not all be zero.
+    /// `n` must not be negative.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let counts = weighted_counts([1, 2, 7], 100);
+    ///
+    /// print(`Out of 100 draws: ${counts}`);
+    /// ```
+    #[cfg(all(feature = "float", feature = "array"))]
+    #[rhai_fn(return_raw, volatile)]
+    pub fn weighted_counts(
+        ctx: NativeCallContext,
+        weights: Array,
+        n: INT,
+    ) -> Result<Array, Box<EvalAltResult>> {
+        if n < 0 {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Invalid number of draws (must not be negative): {}", n),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        let dist = crate::weights::from_array(&weights)?;
+        let mut rng = crate::rng::rng(&ctx);
+        let mut counts: Vec<INT> = vec![0; weights.len()];
+
+        for _ in 0..n {
+            counts[dist.sample(&mut rng)] += 1;
+        }
+
+        Ok(counts.into_iter().map(Dynamic::from_int).collect())
+    }
+
+    /// Return the `index`-th value of the Halton low-discrepancy sequence for `base`,
+    /// between `0.0` and `1.0` (exclusive).
+    /// Requires the `float` feature.
+    ///
+    /// Unlike the other functions in this package, the result is _not_ random: the same
+    /// `index` and `base` always give the same value. Consecutive indices cover the range
+    /// more evenly than random numbers, which is useful for quasi-Monte Carlo integration.
+    ///
+    /// `index` must not be negative, and `base` must be at least `2` (usually a prime number).
+    /// Index `0` always gives `0.0`.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// for i in 1..5 {
+    ///     print(halton(i, 2));   // 0.5, 0.25, 0.75, 0.125
+    /// }
+    /// ```
+    #[cfg(feature = "float")]
+    #[rhai_fn(return_raw, volatile)]
+    pub fn halton(index: INT, base: INT) -> Result<FLOAT, Box<EvalAltResult>> {
+        if index < 0 {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Invalid index (must not be negative): {}", index),
+                Position::NONE,
+            )
+            .into());
+        }
+        if base < 2 {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Invalid base (must be at least 2): {}", base),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        Ok(super::radical_inverse(index, base))
+    }
+
+    /// Return the `index`-th point of a 2D low-discrepancy sequence in the unit square,
+    /// as an `[x, y]` array, using the Halton sequences for bases `2` and `3`.
+    /// Requires the `float` and `array` features.
+    ///
+    /// Like `halton`, the result is _not_ random. A run of consecutive indices covers the
+    /// unit square more evenly than the same number of random points.
+    ///
+    /// `index` must not be negative.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// for i in 1..=100 {
+    ///     let point = quasi_point_2d(i);
+    ///
+    ///     print(`Sample at (${point[0]}, ${point[1]})`);
+    /// }
+    /// ```
+    #[cfg(all(feature = "float", feature = "array"))]
+    #[rhai_fn(return_raw, volatile)]
+    pub fn quasi_point_2d(index: INT) -> Result<Array, Box<EvalAltResult>> {
+        if index < 0 {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Invalid index (must not be negative): {}", index),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        Ok(vec![
+            super::radical_inverse(index, 2).into(),
+            super::radical_inverse(index, 3).into(),
+        ])
+    }
+
+    /// Generate a random integer number within an inclusive range that satisfies a predicate.
+    ///
+    /// Random numbers between `start` and `end` (inclusive) are drawn and passed to `predicate`
+    /// until it returns `true`, giving up after `max_tries` attempts.
+    ///
+    /// If no number satisfying `predicate` is found, `()` is returned.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let number = rand_int_where(|x| x % 7 == 0, 1, 100, 1000);
+    ///
+    /// print(`I'll give you a random multiple of 7 between 1 and 100: ${number}`);
+    /// ```
+    #[rhai_fn(return_raw, volatile)]
+    pub fn rand_int_where(
+        ctx: NativeCallContext,
+        predicate: FnPtr,
+        start: INT,
+        end: INT,
+        max_tries: INT,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        if start > end {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Range is empty: {}..{}", start, end),
+                Position::NONE,
+            )
+            .into());
+        }
+        if max_tries < 1 {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "Invalid number of tries (must be at least 1): {}",
+                    max_tries
+                ),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        for attempts in 1..=max_tries as usize {
+            let value = crate::rng::rng(&ctx).gen_range(start..=end);
+
+            if predicate.call_within_context::<bool>(&ctx, (value,))? {
+                super::record_rejection_stats(attempts, 1);
+                return Ok(value.into());
+            }
+        }
+
+        super::record_rejection_stats(max_tries as usize, 0);
+
+        Ok(Dynamic::UNIT)
+    }
+
+    /// Create a sampler for the empirical distribution of an array of observed numbers.
+    /// Requires the `float` and `array` features.
+    ///
+    /// Use `draw` on the returned sampler to generate random values from the distribution.
+    ///
+    /// `samples` must be a non-empty array of numbers.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let sampler = empirical_sampler([1.2, 3.4, 2.2, 5.0, 2.8]);
+    ///
+    /// let number = sampler.draw();
+    ///
+    /// print(`I'll give you a random number like the ones observed: ${number}`);
+    /// ```
+    #[cfg(all(feature = "float", feature = "array"))]
+    #[rhai_fn(return_raw, volatile)]
+    pub fn empirical_sampler(samples: Array) -> Result<EmpiricalSampler, Box<EvalAltResult>> {
+        if samples.is_empty() {
+            return Err(EvalAltResult::ErrorArithmetic(
+                "Samples must not be empty".to_string(),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        let mut sorted = samples
+            .iter()
+            .map(|v| super::to_float(v).filter(|v| v.is_finite()))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| {
+                EvalAltResult::ErrorArithmetic(
+                    "Samples must be finite numbers".to_string(),
+                    Position::NONE,
+                )
+            })?;
+
+        sorted.sort_by(|a, b| a.total_cmp(b));
+
+        Ok(EmpiricalSampler { sorted })
+    }
+
+    /// Generate a random floating-point number from an empirical distribution.
+    /// Requires the `float` and `array` features.
+    ///
+    /// The result is interpolated linearly between the sorted observed values, so it always
+    /// lies between the smallest and largest observed values (inclusive).
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let sampler = empirical_sampler([1.2, 3.4, 2.2, 5.0, 2.8]);
+    ///
+    /// for i in 0..10 {
+    ///     print(sampler.draw());
+    /// }
+    /// ```
+    #[cfg(all(feature = "float", feature = "array"))]
+    #[rhai_fn(global, name = "draw", volatile)]
+    pub fn draw_empirical(ctx: NativeCallContext, sampler: &mut EmpiricalSampler) -> FLOAT {
+        let sorted = &sampler.sorted;
+
+        if sorted.len() == 1 {
+            return sorted[0];
+        }
+
+        let position = crate::rng::rng(&ctx).gen_range(0.0..=1.0) * (sorted.len() - 1) as FLOAT;
+        let index = (position as usize).min(sorted.len() - 2);
+        let fraction = position - index as FLOAT;
+
+        sorted[index] + fraction * (sorted[index + 1] - sorted[index])
+    }
+
+    /// Randomly descend a tree of weights and return the path of keys taken.
+    /// Requires the `float` and `array` features.
+    ///
+    /// `tree` is an object map where each value is either a non-negative number (a leaf weight)
+    /// or another object map (a subtree). At each level, a key is chosen with a probability
+    /// proportional to its weight, where the weight of a subtree is the total of its leaves.
+    /// Each leaf is therefore reached with a probability proportional to its own weight.
+    ///
+    /// No level may be empty, and the total weight must not be zero.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let menu = #{
+    ///     drinks: #{ coffee: 5, tea: 3 },
+    ///     food: #{ cake: 1, sandwich: 2 }
+    /// };
+    ///
+    /// let path = sample_tree(menu);
+    ///
+    /// print(`Today's choice: ${path[0]} / ${path[1]}`);
+    /// ```
+    #[cfg(all(feature = "float", feature = "array"))]
+    #[rhai_fn(return_raw, volatile)]
+    pub fn sample_tree(
+        ctx: NativeCallContext,
+        tree: rhai::Map,
+    ) -> Result<Array, Box<EvalAltResult>> {
+        let mut rng = crate::rng::rng(&ctx);
+        let mut path = Array::new();
+        let mut node = Dynamic::from_map(tree);
+
+        loop {
+            let child = {
+                let map = match node.read_lock::<rhai::Map>() {
+                    Some(map) => map,
+                    None => break,
+                };
+
+                let weights = map
+                    .values()
+                    .map(super::tree_weight)
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                let dist = crate::weights::from_values(&weights)?;
+
+                let (key, child) = map.iter().nth(dist.sample(&mut rng)).unwrap();
+                path.push(key.clone().into());
+                child.clone()
+            };
+
+            node = child;
+        }
+
+        Ok(path)
+    }
+
+    /// Generate a random leading digit between `1` and `9` following Benford's law,
+    /// where digit `d` has a probability of `log10(1 + 1/d)`.
+    ///
+    /// This is useful for generating realistic-looking numbers (e.g. financial data),
+    /// where `1` is the leading digit about 30% of the time.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let digit = rand_benford_digit();
+    ///
+    /// print(`I'll give you a random leading digit: ${digit}`);
+    /// ```
+    #[rhai_fn(volatile)]
+    pub fn rand_benford_digit(ctx: NativeCallContext) -> INT {
+        // Inverse transform: the CDF of the first `d` digits is `log10(d + 1)`.
+        let u: f64 = crate::rng::rng(&ctx).gen();
+
+        (10.0_f64.powf(u).floor() as INT).clamp(1, 9)
+    }
+
+    /// Make `n` random draws (with replacement) from the entries of an object map, each chosen
+    /// with a probability proportional to the numeric `weight_field` of its value.
+    /// Requires the `float` and `array` features.
+    ///
+    /// Every value in `map` must be an object map containing `weight_field` with a non-negative
+    /// number, and the weights must not all be zero. `n` must not be negative or exceed
+    /// 1,048,576.
+    ///
+    /// Each chosen entry is returned as an object map with two fields: `key` and `value`.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let loot = #{
+    ///     gold: #{ weight: 10, amount: 100 },
+    ///     gem: #{ weight: 1, amount: 1 }
+    /// };
+    ///
+    /// for entry in sample_entries(loot, "weight", 3) {
+    ///     print(`You found ${entry.value.amount} ${entry.key}`);
+    /// }
+    /// ```
+    #[cfg(all(feature = "float", feature = "array"))]
+    #[rhai_fn(return_raw, volatile)]
+    pub fn sample_entries(
+        ctx: NativeCallContext,
+        map: rhai::Map,
+        weight_field: &str,
+        n: INT,
+    ) -> Result<Array, Box<EvalAltResult>> {
+        if n < 0 {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Invalid number of draws (must not be negative): {}", n),
+                Position::NONE,
+            )
+            .into());
+        }
+        super::check_array_length(n)?;
+
+        let weights = map
+            .iter()
+            .map(|(key, value)| {
+                let weight = value
+                    .read_lock::<rhai::Map>()
+                    .and_then(|entry| entry.get(weight_field).cloned())
+                    .ok_or_else(|| {
+                        EvalAltResult::ErrorArithmetic(
+                            format!("Entry '{}' has no '{}' field", key, weight_field),
+                            Position::NONE,
+                        )
+                    })?;
+
+                crate::weights::to_weight(&weight)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let dist = crate::weights::from_values(&weights)?;
+        let entries: Vec<_> = map.into_iter().collect();
+        let mut rng = crate::rng::rng(&ctx);
+
+        Ok((0..n)
+            .map(|_| {
+                let (key, value) = entries[dist.sample(&mut rng)].clone();
+                let mut entry = rhai::Map::new();
+                entry.insert("key".into(), key.into());
+                entry.insert("value".into(), value);
+                entry.into()
+            })
+            .collect())
+    }
+
+    /// Generate an array of `n` spatially-correlated random numbers (a 1D Gaussian random field),
+    /// each following the standard normal distribution.
+    /// Requires the `float` and `array` features.
+    ///
+    /// Neighboring values are correlated over roughly `correlation_length` positions, so longer
+    /// correlation lengths give smoother fields. The field is generated as a moving average of
+    /// independent normal noise over a window of `correlation_length` (rounded) positions.
+    ///
+    /// `n` must not be negative or exceed 1,048,576, and `correlation_length` must be positive.
+    /// Correlation lengths longer than 1,048,576 are treated as 1,048,576.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let terrain = gaussian_field(100, 8.0);
+    ///
+    /// print(`Smooth random heights: ${terrain}`);
+    /// ```
+    #[cfg(all(feature = "float", feature = "array"))]
+    #[rhai_fn(return_raw, volatile)]
+    pub fn gaussian_field(
+        ctx: NativeCallContext,
+        n: INT,
+        correlation_length: FLOAT,
+    ) -> Result<Array, Box<EvalAltResult>> {
+        if n < 0 {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Invalid number of values (must not be negative): {}", n),
+                Position::NONE,
+            )
+            .into());
+        }
+        super::check_array_length(n)?;
+        if !(correlation_length > 0.0 && correlation_length.is_finite()) {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "Invalid correlation length (must be positive): {}",
+                    correlation_length
+                ),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        let window = (correlation_length.min(super::MAX_LENGTH as FLOAT).round() as usize).max(1);
+        let mut rng = crate::rng::rng(&ctx);
+        let noise: Vec<FLOAT> = (0..n as usize + window - 1)
+            .map(|_| rng.sample(StandardNormal))
+            .collect();
+
+        // Scale the sum of `window` independent normals back to unit variance
+        let scale = (window as FLOAT).sqrt();
+
+        Ok(noise
+            .windows(window)
+            .map(|w| (w.iter().sum::<FLOAT>() / scale).into())
+            .collect())
+    }
+
+    /// Return the acceptance ratio (accepted samples divided by attempts) of the most recent
+    /// call to a rejection-based function, such as `rand_in_polygon`, `rand_int_where`
+    /// or `rand_von_mises`.
+    /// Requires the `float` feature.
+    ///
+    /// Low ratios mean that most attempts were wasted, which indicates that the parameters
+    /// make sampling inefficient. `1.0` is returned if no rejection-based function has been
+    /// called yet (on the current thread).
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let point = rand_in_polygon([[0, 0], [10, 0], [0, 1]]);
+    ///
+    /// print(`Acceptance ratio: ${last_rejection_ratio()}`);
+    /// ```
+    #[cfg(feature = "float")]
+    #[rhai_fn(volatile)]
+    pub fn last_rejection_ratio() -> FLOAT {
+        super::REJECTION_STATS.with(|stats| match stats.get() {
+            (0, _) => 1.0,
+            (attempts, accepted) => accepted as FLOAT / attempts as FLOAT,
+        })
+    }
+
+    /// Generate a random integer number between `0` and `modulus` (exclusive), without the
+    /// bias towards low values caused by `rand() % modulus`.
+    ///
+    /// `modulus` must be positive.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let number = rand_modular(6);
+    ///
+    /// print(`I'll give you a random number between 0 and 5: ${number}`);
+    /// ```
+    #[rhai_fn(return_raw, volatile)]
+    pub fn rand_modular(ctx: NativeCallContext, modulus: INT) -> Result<INT, Box<EvalAltResult>> {
+        if modulus <= 0 {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Invalid modulus (must be positive): {}", modulus),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        let modulus = modulus as u64;
+        // Reject the top `2^64 % modulus` values so that every remainder is equally likely.
+        let limit = u64::MAX - (u64::MAX % modulus + 1) % modulus;
+        let mut rng = crate::rng::rng(&ctx);
+        let mut attempts = 0;
+
+        loop {
+            attempts += 1;
+
+            let value: u64 = rng.gen();
+
+            if value <= limit {
+                super::record_rejection_stats(attempts, 1);
+                return Ok((value % modulus) as INT);
+            }
+        }
+    }
+
+    /// Generate an array of `n` antithetic pairs of random floating-point numbers between
+    /// `0.0` and `1.0`, for variance reduction in Monte Carlo simulations.
+    /// Requires the `float` and `array` features.
+    ///
+    /// The returned array has `2 * n` elements, where each pair of elements `2i` and `2i + 1`
+    /// is `u` and `1.0 - u` for a random `u`.
+    ///
+    /// `n` must not be negative or exceed 524,288.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let values = antithetic_array(3);
+    ///
+    /// print(`Three pairs of values, each summing to 1: ${values}`);
+    /// ```
+    #[cfg(all(feature = "float", feature = "array"))]
+    #[rhai_fn(return_raw, volatile)]
+    pub fn antithetic_array(ctx: NativeCallContext, n: INT) -> Result<Array, Box<EvalAltResult>> {
+        if n < 0 {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Invalid number of pairs (must not be negative): {}", n),
+                Position::NONE,
+            )
+            .into());
+        }
+        super::check_array_length(n.saturating_mul(2))?;
+
+        let mut rng = crate::rng::rng(&ctx);
+        let mut values = Array::with_capacity(2 * n as usize);
+
+        for _ in 0..n {
+            let u: FLOAT = rng.gen();
+            values.push(u.into());
+            values.push((1.0 - u).into());
+        }
+
+        Ok(values)
+    }
+
+    /// Roll `dice` independent dice with `faces` sides each, and return the sum.
+    ///
+    /// Unlike `rand(dice, dice * faces)`, the result follows the bell-shaped distribution of
+    /// real dice sums, where values near the middle of the range are the most likely.
+    ///
+    /// `dice` must be between `1` and 1,048,576, and `faces` must be at least `1`.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let total = dice_sum(3, 6);
+    ///
+    /// print(`You rolled 3d6 for a total of ${total}`);
+    /// ```
+    #[rhai_fn(return_raw, volatile)]
+    pub fn dice_sum(
+        ctx: NativeCallContext,
+        dice: INT,
+        faces: INT,
+    ) -> Result<INT, Box<EvalAltResult>> {
+        if dice < 1 {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Invalid number of dice (must be at least 1): {}", dice),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        self::dice(ctx, dice, faces)
+    }
+
+    /// Roll `count` independent dice with `sides` sides each (numbered from `1` to `sides`),
+    /// and return the sum, e.g. `dice(2, 6)` for `2d6`. This is the same as `dice_sum`,
+    /// except that rolling zero dice returns `0`.
+    ///
+    /// `count` must be between `0` and 1,048,576, and `sides` must be at least `1`.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let damage = dice(2, 6) + 3;
+    ///
+    /// print(`The goblin hits you for ${damage} damage`);
+    /// ```
+    #[rhai_fn(return_raw, volatile)]
+    pub fn dice(ctx: NativeCallContext, count: INT, sides: INT) -> Result<INT, Box<EvalAltResult>> {
+        super::check_dice(count, sides)?;
+
+        let mut rng = crate::rng::rng(&ctx);
+
+        Ok((0..count).map(|_| rng.gen_range(1..=sides)).sum())
+    }
+
+    /// Roll `count` independent dice with `sides` sides each (numbered from `1` to `sides`),
+    /// and return the individual rolls as an array.
+    /// Requires the `array` feature.
+    ///
+    /// `count` must be between `0` and 1,048,576, and `sides` must be at least `1`.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let rolls = dice_roll(4, 6);
+    ///
+    /// rolls.sort();
+    /// rolls.remove(0);    // drop the lowest roll
+    ///
+    /// print(`Your strength score is ${rolls.reduce(|sum, v| sum + v, 0)}`);
+    /// ```
+    #[cfg(feature = "array")]
+    #[rhai_fn(return_raw, volatile)]
+    pub fn dice_roll(
+        ctx: NativeCallContext,
+        count: INT,
+        sides: INT,
+    ) -> Result<Array, Box<EvalAltResult>> {
+        super::check_dice(count, sides)?;
+
+        let mut rng = crate::rng::rng(&ctx);
+
+        Ok((0..count)
+            .map(|_| rng.gen_range(1..=sides).into())
+            .collect())
+    }
+
+    /// Generate a random floating-point number from a hyperexponential distribution,
+    /// i.e. a mixture of exponential distributions.
+    /// Requires the `float` and `array` features.
+    ///
+    /// A phase is first chosen with a probability proportional to `weights`, then a number is
+    /// drawn from the exponential distribution with that phase's rate in `rates`.
+    /// This is useful for modeling heavy-tailed service times.
+    ///
+    /// `rates` must be positive numbers, and `weights` must be non-negative numbers (not all zero)
+    /// with the same length as `rates`.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// // 90% of requests are fast (mean 0.1s), 10% are slow (mean 5s)
+    /// let time = rand_hyperexponential([10.0, 0.2], [9, 1]);
+    ///
+    /// print(`Service time: ${time}s`);
+    /// ```
+    #[cfg(all(feature = "float", feature = "array"))]
+    #[rhai_fn(return_raw, volatile)]
+    pub fn rand_hyperexponential(
+        ctx: NativeCallContext,
+        rates: Array,
+        weights: Array,
+    ) -> Result<FLOAT, Box<EvalAltResult>> {
+        if weights.len() != rates.len() {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "Number of weights ({}) does not match the number of rates ({})",
+                    weights.len(),
+                    rates.len()
+                ),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        let rates = rates
+            .iter()
+            .map(|r| super::to_float(r).filter(|&r| r > 0.0 && r.is_finite()))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| {
+                EvalAltResult::ErrorArithmetic(
+                    "Rates must be positive numbers".to_string(),
+                    Position::NONE,
+                )
+            })?;
+
+        let dist = crate::weights::from_array(&weights)?;
+        let mut rng = crate::rng::rng(&ctx);
+        let rate = rates[dist.sample(&mut rng)];
+        let x: FLOAT = rng.sample(Exp1);
+
+        Ok(x / rate)
+    }
+
+    /// Generate a random permutation of the integers `0` to `n - 1`, expressed as its cycles.
+    /// Requires the `array` feature.
+    ///
+    /// The result is an array of cycles, each being an array of indices where every index is
+    /// mapped to the next one in the cycle, and the last index is mapped back to the first.
+    /// Each cycle starts with its smallest index, and the cycles are ordered by their first index.
+    ///
+    /// `n` must not be negative or exceed 1,048,576.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let cycles = rand_permutation_cycles(5);
+    ///
+    /// print(`Permutation cycles: ${cycles}`);   // e.g. [[0, 3], [1], [2, 4]]
+    /// ```
+    #[cfg(feature = "array")]
+    #[rhai_fn(return_raw, volatile)]
+    pub fn rand_permutation_cycles(
+        ctx: NativeCallContext,
+        n: INT,
+    ) -> Result<Array, Box<EvalAltResult>> {
+        if n < 0 {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Invalid number of elements (must not be negative): {}", n),
+                Position::NONE,
+            )
+            .into());
+        }
+        super::check_array_length(n)?;
+
+        let mut permutation: Vec<usize> = (0..n as usize).collect();
+        permutation.shuffle(&mut crate::rng::rng(&ctx));
+
+        let mut visited = vec![false; permutation.len()];
+        let mut cycles = Array::new();
+
+        for start in 0..permutation.len() {
+            if visited[start] {
+                continue;
+            }
+
+            let mut cycle = Array::new();
+            let mut index = start;
+
+            while !visited[index] {
+                visited[index] = true;
+                cycle.push((index as INT).into());
+                index = permutation[index];
+            }
+
+            cycles.push(cycle.into());
+        }
+
+        Ok(cycles)
+    }
+
+    /// Generate an array of `n` random floating-point numbers following a normal distribution
+    /// with the specified `mean` and standard deviation `std`, truncated to the range
+    /// `[min, max]`.
+    /// Requires the `float` and `array` features.
+    ///
+    /// Values are generated by inverting the cumulative distribution function, so no samples
+    /// are rejected even when the range lies far from the mean.
+    ///
+    /// `n` must not be negative or exceed 1,048,576, `std` must be positive, and `min` must be
+    /// less than `max`.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let stats = truncated_normal_array(6, 10.0, 3.0, 3.0, 18.0);
+    ///
+    /// print(`Character stats: ${stats}`);
+    /// ```
+    #[cfg(all(feature = "float", feature = "array"))]
+    #[rhai_fn(return_raw, volatile)]
+    pub fn truncated_normal_array(
+        ctx: NativeCallContext,
+        n: INT,
+        mean: FLOAT,
+        std: FLOAT,
+        min: FLOAT,
+        max: FLOAT,
+    ) -> Result<Array, Box<EvalAltResult>> {
+        if n < 0 {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Invalid number of values (must not be negative): {}", n),
+                Position::NONE,
+            )
+            .into());
+        }
+        super::check_array_length(n)?;
+        if !mean.is_finite() {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Invalid mean (must be finite): {}", mean),
+                Position::NONE,
+            )
+            .into());
+        }
+        if !(std > 0.0 && std.is_finite()) {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Invalid standard deviation (must be positive): {}", std),
+                Position::NONE,
+            )
+            .into());
+        }
+        if min >= max || min.is_nan() || max.is_nan() {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Range is empty: {}..{}", min, max),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        let mut rng = crate::rng::rng(&ctx);
+
+        Ok((0..n)
+            .map(|_| super::sample_truncated_normal(&mut rng, mean, std, min, max).into())
+            .collect())
+    }
+
+    /// Select up to `n` distinct indices, each chosen with a probability proportional to its
+    /// weight in `weights`.
+    /// Requires the `float` and `array` features.
+    ///
+    /// This is useful when the elements themselves are stored elsewhere, and only their weights
+    /// are at hand. Indices with zero weight are never selected, so fewer than `n` indices are
+    /// returned if there are not enough positive weights.
+    ///
+    /// The selected indices are returned in the order they were picked.
+    ///
+    /// `weights` must be an array of non-negative numbers, and `n` must not be negative.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let weights = [5, 1, 1, 3];
+    ///
+    /// let picks = weighted_indices(weights, 2);
+    ///
+    /// print(`Two distinct indices, most likely 0 and 3: ${picks}`);
+    /// ```
+    #[cfg(all(feature = "float", feature = "array"))]
+    #[rhai_fn(return_raw, volatile)]
+    pub fn weighted_indices(
+        ctx: NativeCallContext,
+        weights: Array,
+        n: INT,
+    ) -> Result<Array, Box<EvalAltResult>> {
+        if n < 0 {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "Invalid number of indices to select (must not be negative): {}",
+                    n
+                ),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        let mut weights = weights
+            .iter()
+            .map(crate::weights::to_weight)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // All-zero weights simply select nothing, so only normalize when there is something
+        // to select.
+        if weights.iter().any(|&weight| weight > 0.0) {
+            weights = crate::weights::normalize(&weights)?;
+        }
+
+        let mut rng = crate::rng::rng(&ctx);
+
+        // A-Res: keying each index by `u^(1/weight)` and taking the `n` largest keys draws
+        // `n` indices without replacement, proportionally to their weights. Logarithms of the
+        // keys are compared instead, to avoid underflow for small weights.
+        let mut keys: Vec<(FLOAT, usize)> = weights
+            .iter()
+            .enumerate()
+            .filter(|(_, &weight)| weight > 0.0)
+            .map(|(index, &weight)| {
+                let u: FLOAT = 1.0 - rng.gen::<FLOAT>();
+                (u.ln() / weight, index)
+            })
+            .collect();
+
+        keys.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        Ok(keys
+            .into_iter()
+            .take(n as usize)
+            .map(|(_, index)| (index as INT).into())
+            .collect())
+    }
+
+    /// Generate a random boolean value that is `true` with odds of `odds_for` to `odds_against`,
+    /// i.e. with a probability of `odds_for / (odds_for + odds_against)`.
+    ///
+    /// `odds_for` and `odds_against` must both be positive.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let win = rand_bool_odds(3, 1);     // 3 to 1 odds, i.e. 75% probability
+    ///
+    /// if win {
+    ///     print("Your bet paid off!")
+    /// }
+    /// ```
+    #[rhai_fn(return_raw, volatile)]
+    pub fn rand_bool_odds(
+        ctx: NativeCallContext,
+        odds_for: INT,
+        odds_against: INT,
+    ) -> Result<bool, Box<EvalAltResult>> {
+        if odds_for <= 0 || odds_against <= 0 {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "Invalid odds (must both be positive): {} to {}",
+                    odds_for, odds_against
+                ),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        // The sum of two positive `INT`'s always fits in a `u64`
+        let total = odds_for as u64 + odds_against as u64;
+
+        Ok(crate::rng::rng(&ctx).gen_range(0..total) < odds_for as u64)
+    }
+
+    /// Generate a random boolean value that is `true` with a probability of
+    /// `numerator / denominator`, e.g. `rand_ratio(1, 20)` for a "1 in 20" chance.
+    ///
+    /// `denominator` must be positive, and `numerator` must be between `0` and `denominator`
+    /// (inclusive).
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let critical = rand_ratio(1, 20);   // 1 in 20 chance
+    ///
+    /// if critical {
+    ///     print("Critical hit!")
+    /// }
+    /// ```
+    #[rhai_fn(return_raw, volatile)]
+    pub fn rand_ratio(
+        ctx: NativeCallContext,
+        numerator: INT,
+        denominator: INT,
+    ) -> Result<bool, Box<EvalAltResult>> {
+        if denominator <= 0 || !(0..=denominator).contains(&numerator) {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "Invalid ratio (must be between 0 and 1 with a positive denominator): {}/{}",
+                    numerator, denominator
+                ),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        // Unlike `Rng::gen_ratio`, this is not limited to 32-bit ratios
+        Ok(crate::rng::rng(&ctx).gen_range(0..denominator) < numerator)
+    }
+
+    /// Generate an array of `n` random floating-point numbers that start at `start` and halve
+    /// every `half_life` steps, each multiplied by random log-normal noise.
+    /// Requires the `float` and `array` features.
+    ///
+    /// The `i`-th value is `start * 0.5 ** (i / half_life) * exp(noise * z)`, where `z` follows
+    /// a standard normal distribution. A `noise` of `0.0` follows the decay curve exactly.
+    ///
+    /// `start` must be positive, `half_life` must be at least `1`, `noise` must not be negative,
+    /// and `n` must not be negative or exceed 1,048,576.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let magnitudes = log_decay_series(100.0, 5, 0.2, 20);
+    ///
+    /// print(`Aftershock magnitudes: ${magnitudes}`);
+    /// ```
+    #[cfg(all(feature = "float", feature = "array"))]
+    #[rhai_fn(return_raw, volatile)]
+    pub fn log_decay_series(
+        ctx: NativeCallContext,
+        start: FLOAT,
+        half_life: INT,
+        noise: FLOAT,
+        n: INT,
+    ) -> Result<Array, Box<EvalAltResult>> {
+        if !(start > 0.0 && start.is_finite()) {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Invalid start value (must be positive): {}", start),
+                Position::NONE,
+            )
+            .into());
+        }
+        if half_life < 1 {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Invalid half-life (must be at least 1): {}", half_life),
+                Position::NONE,
+            )
+            .into());
+        }
+        if noise < 0.0 || !noise.is_finite() {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Invalid noise (must not be negative): {}", noise),
+                Position::NONE,
+            )
+            .into());
+        }
+        if n < 0 {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Invalid number of values (must not be negative): {}", n),
+                Position::NONE,
+            )
+            .into());
+        }
+        super::check_array_length(n)?;
+
+        let mut rng = crate::rng::rng(&ctx);
+
+        Ok((0..n)
+            .map(|i| {
+                let decay = (0.5 as FLOAT).powf(i as FLOAT / half_life as FLOAT);
+                let z: FLOAT = rng.sample(StandardNormal);
+
+                (start * decay * (noise * z).exp()).into()
+            })
+            .collect())
+    }
+
+    /// Generate a random integer number chosen uniformly from a union of inclusive ranges.
+    /// Requires the `array` feature.
+    ///
+    /// `ranges` must be a non-empty array of `[start, end]` pairs of integers, each describing
+    /// the inclusive range `start..=end`, with `start` not greater than `end`.
+    /// The ranges must not overlap.
+    ///
+    /// Every integer in the union is equally likely, so larger ranges are chosen more often.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let port = rand_int_union([[1024, 5999], [7000, 8079], [8090, 49151]]);
+    ///
+    /// print(`Listening on port ${port}`);
+    /// ```
+    #[cfg(feature = "array")]
+    #[rhai_fn(return_raw, volatile)]
+    pub fn rand_int_union(
+        ctx: NativeCallContext,
+        ranges: Array,
+    ) -> Result<INT, Box<EvalAltResult>> {
+        let mut bounds = ranges
+            .iter()
+            .map(|range| {
+                let pair = range
+                    .read_lock::<Array>()
+                    .filter(|pair| pair.len() == 2)
+                    .and_then(|pair| Some((pair[0].as_int().ok()?, pair[1].as_int().ok()?)));
+
+                match pair {
+                    Some((start, end)) if start <= end => Ok((start, end)),
+                    Some((start, end)) => Err(EvalAltResult::ErrorArithmetic(
+                        format!("Range is empty: {}..={}", start, end),
+                        Position::NONE,
+                    )
+                    .into()),
+                    None => Err(EvalAltResult::ErrorArithmetic(
+                        format!("Range must be a [start, end] pair of integers: {}", range),
+                        Position::NONE,
+                    )
+                    .into()),
+                }
+            })
+            .collect::<Result<Vec<_>, Box<EvalAltResult>>>()?;
+
+        if bounds.is_empty() {
+            return Err(EvalAltResult::ErrorArithmetic(
+                "Ranges must not be empty".to_string(),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        bounds.sort_unstable();
+
+        if let Some(pair) = bounds.windows(2).find(|pair| pair[1].0 <= pair[0].1) {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "Ranges must not overlap: {}..={} and {}..={}",
+                    pair[0].0, pair[0].1, pair[1].0, pair[1].1
+                ),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        // Sizes are computed in `u128` since a single range may cover every `INT`
+        let size = |(start, end): (INT, INT)| (end as i128 - start as i128 + 1) as u128;
+        let total: u128 = bounds.iter().copied().map(size).sum();
+        let mut offset = crate::rng::rng(&ctx).gen_range(0..total);
+
+        for &(start, end) in &bounds {
+            if offset < size((start, end)) {
+                return Ok((start as i128 + offset as i128) as INT);
+            }
+            offset -= size((start, end));
+        }
+
+        unreachable!("offset is always within the total size of the ranges")
+    }
+
+    /// Generate an array of `n` random floating-point numbers forming pink noise, i.e. noise
+    /// whose power is inversely proportional to frequency, between `-1.0` and `1.0`.
+    /// Requires the `float` and `array` features.
+    ///
+    /// Unlike independent random numbers (white noise), consecutive values are correlated,
+    /// so slow fluctuations dominate, as in many natural signals.
+    ///
+    /// `n` must not be negative or exceed 1,048,576.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let signal = pink_noise(1024);
+    ///
+    /// print(`First samples: ${signal.extract(0, 8)}`);
+    /// ```
+    #[cfg(all(feature = "float", feature = "array"))]
+    #[rhai_fn(return_raw, volatile)]
+    pub fn pink_noise(ctx: NativeCallContext, n: INT) -> Result<Array, Box<EvalAltResult>> {
+        const ROWS: usize = 16;
+
+        if n < 0 {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Invalid number of values (must not be negative): {}", n),
+                Position::NONE,
+            )
+            .into());
+        }
+        super::check_array_length(n)?;
+
+        let mut rng = crate::rng::rng(&ctx);
+
+        // Voss-McCartney: row `k` is refreshed every `2^k` samples, staggered so that only one
+        // row changes per sample, and the output is the sum of all rows plus a white term.
+        let mut rows: [FLOAT; ROWS] = std::array::from_fn(|_| rng.gen_range(-1.0..=1.0));
+        let mut sum: FLOAT = rows.iter().sum();
+
+        Ok((0..n as u64)
+            .map(|i| {
+                if i > 0 {
+                    let row = (i.trailing_zeros() as usize).min(ROWS - 1);
+                    let value = rng.gen_range(-1.0..=1.0);
+
+                    sum += value - rows[row];
+                    rows[row] = value;
+                }
+
+                let white: FLOAT = rng.gen_range(-1.0..=1.0);
+
+                ((sum + white) / (ROWS + 1) as FLOAT).into()
+            })
+            .collect())
+    }
+
+    /// Generate an array of `n` boolean values, exactly `k` of which are `true`,
+    /// placed at random positions.
+    /// Requires the `array` feature.
+    ///
+    /// `n` must not exceed 1,048,576, and `k` must be between `0` and `n` (inclusive).
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let mines = rand_mask(64, 10);
+    ///
+    /// print(`Mine field: ${mines}`);
+    /// ```
+    #[cfg(feature = "array")]
+    #[rhai_fn(return_raw, volatile)]
+    pub fn rand_mask(ctx: NativeCallContext, n: INT, k: INT) -> Result<Array, Box<EvalAltResult>> {
+        if n < 0 {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Invalid number of values (must not be negative): {}", n),
+                Position::NONE,
+            )
+            .into());
+        }
+        super::check_array_length(n)?;
+        if k < 0 || k > n {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "Invalid number of true values (must be between 0 and {}): {}",
+                    n, k
+                ),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        let mut positions: Vec<usize> = (0..n as usize).collect();
+        let (chosen, _) = positions.partial_shuffle(&mut crate::rng::rng(&ctx), k as usize);
+
+        let mut mask = vec![Dynamic::FALSE; n as usize];
+
+        for &position in chosen.iter() {
+            mask[position] = Dynamic::TRUE;
+        }
+
+        Ok(mask)
+    }
+
+    /// Generate a pair of random floating-point numbers between `0.0` (inclusive) and `1.0`
+    /// (exclusive), correlated through a Gaussian copula with correlation `rho`.
+    /// Requires the `float` and `array` features.
+    ///
+    /// Each number on its own is uniformly distributed, but positive `rho` values make them
+    /// tend to be both high or both low, while negative values make one high when the other
+    /// is low. The pair is returned as an array `[u, v]`.
+    ///
+    /// `rho` must be between `-1.0` and `1.0` (exclusive).
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let pair = gaussian_copula_2d(0.8);
+    ///
+    /// print(`Correlated uniforms: ${pair[0]} and ${pair[1]}`);
+    /// ```
+    #[cfg(all(feature = "float", feature = "array"))]
+    #[rhai_fn(return_raw, volatile)]
+    pub fn gaussian_copula_2d(
+        ctx: NativeCallContext,
+        rho: FLOAT,
+    ) -> Result<Array, Box<EvalAltResult>> {
+        if !(rho > -1.0 && rho < 1.0) {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "Invalid correlation (must be between -1.0 and 1.0, exclusive): {}",
+                    rho
+                ),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        let mut rng = crate::rng::rng(&ctx);
+        let x: FLOAT = rng.sample(StandardNormal);
+        let z: FLOAT = rng.sample(StandardNormal);
+        let y = rho * x + (1.0 - rho * rho).sqrt() * z;
+
+        // Keep results strictly below `1.0`, as the normal CDF rounds up far in the upper tail
+        let below_one = 1.0 - FLOAT::EPSILON / 2.0;
+
+        Ok(vec![
+            super::normal_cdf(x).min(below_one).into(),
+            super::normal_cdf(y).min(below_one).into(),
+        ])
+    }
+
+    /// Generate a random non-negative integer number with exactly `set_bits` bits set to one,
+    /// at random positions among its lowest `total_bits` bits.
+    ///
+    /// The result is always less than `2 ** total_bits`.
+    ///
+    /// `set_bits` must be between `0` and `total_bits` (inclusive), and `total_bits` must be
+    /// between `0` and `63` (inclusive).
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let flags = rand_bits_set(8, 3);
+    ///
+    /// print(`Three of eight flags set: ${flags}`);
+    /// ```
+    #[rhai_fn(return_raw, volatile)]
+    pub fn rand_bits_set(
+        ctx: NativeCallContext,
+        total_bits: INT,
+        set_bits: INT,
+    ) -> Result<INT, Box<EvalAltResult>> {
+        if !(0..=63).contains(&total_bits) {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "Invalid number of bits (must be between 0 and 63): {}",
+                    total_bits
+                ),
+                Position::NONE,
+            )
+            .into());
+        }
+        if !(0..=total_bits).contains(&set_bits) {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "Invalid number of set bits (must be between 0 and {}): {}",
+                    total_bits, set_bits
+                ),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        let positions = rand::seq::index::sample(
+            &mut crate::rng::rng(&ctx),
+            total_bits as usize,
+            set_bits as usize,
+        );
+
+        Ok(positions
+            .into_iter()
+            .fold(0, |value, position| value | (1 << position)))
+    }
+
+    /// Generate a random point along the Catmull-Rom spline through `control_points`,
+    /// and return it as an `[x, y]` array.
+    /// Requires the `float` and `array` features.
+    ///
+    /// The spline passes through every control point except the first and the last, which only
+    /// shape the curve at its ends. A random position along the spline is chosen by picking a
+    /// random segment between two consecutive control points, and a random parameter within it.
+    ///
+    /// `control_points` must be an array of at least four `[x, y]` points.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let path = [[0.0, 0.0], [1.0, 2.0], [3.0, 3.0], [5.0, 1.0], [6.0, 0.0]];
+    ///
+    /// let point = sample_spline(path);
+    ///
+    /// print(`Somewhere along the path: ${point}`);
+    /// ```
+    #[cfg(all(feature = "float", feature = "array"))]
+    #[rhai_fn(return_raw, volatile)]
+    pub fn sample_spline(
+        ctx: NativeCallContext,
+        control_points: Array,
+    ) -> Result<Array, Box<EvalAltResult>> {
+        let points = control_points
+            .iter()
+            .map(|v| super::to_vector(v, 2))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| {
+                EvalAltResult::ErrorArithmetic(
+                    "Spline control points must be [x, y] points".to_string(),
+                    Position::NONE,
+                )
+            })?;
+
+        if points.len() < 4 {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "Spline must have at least four control points: {}",
+                    points.len()
+                ),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        let mut rng = crate::rng::rng(&ctx);
+        let segment = rng.gen_range(0..points.len() - 3);
+        let t: FLOAT = rng.gen();
+        let (t2, t3) = (t * t, t * t * t);
+        let [p0, p1, p2, p3] = [0, 1, 2, 3].map(|i| &points[segment + i]);
+
+        Ok((0..2)
+            .map(|axis| {
+                let value = 0.5
+                    * (2.0 * p1[axis]
+                        + (p2[axis] - p0[axis]) * t
+                        + (2.0 * p0[axis] - 5.0 * p1[axis] + 4.0 * p2[axis] - p3[axis]) * t2
+                        + (3.0 * p1[axis] - p0[axis] - 3.0 * p2[axis] + p3[axis]) * t3);
+
+                value.into()
+            })
+            .collect())
+    }
+
+    /// Create a weighted sampler over the indices of an array of weights, whose weights can be
+    /// updated later without rebuilding the sampler.
+    /// Requires the `float` and `array` features.
+    ///
+    /// Use `draw` on the returned sampler to generate random indices, each chosen with a
+    /// probability proportional to its weight, and `update_weight` to change a weight.
+    ///
+    /// `weights` must be a non-empty array of non-negative numbers.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let sampler = weighted_sampler([1, 1, 1]);
+    ///
+    /// sampler.update_weight(2, 10.0);
+    ///
+    /// let index = sampler.draw();
+    ///
+    /// print(`Most likely index 2: ${index}`);
+    /// ```
+    #[cfg(all(feature = "float", feature = "array"))]
+    #[rhai_fn(return_raw, volatile)]
+    pub fn weighted_sampler(weights: Array) -> Result<WeightedSampler, Box<EvalAltResult>> {
+        if weights.is_empty() {
+            return Err(EvalAltResult::ErrorArithmetic(
+                "Weights must not be empty".to_string(),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        let weights = weights
+            .iter()
+            .map(crate::weights::to_weight)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(WeightedSampler::new(weights))
+    }
+
+    /// Change the weight of an index in a weighted sampler.
+    /// Requires the `float` and `array` features.
+    ///
+    /// `index` must be a valid index of the sampler's weights, and `weight` must be a
+    /// non-negative number (integer or floating-point).
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let sampler = weighted_sampler([5, 1, 1]);
+    ///
+    /// sampler.update_weight(0, 0.0);      // index 0 is never drawn from now on
+    ///
+    /// print(sampler.draw());
+    /// ```
+    #[cfg(all(feature = "float", feature = "array"))]
+    #[rhai_fn(global, return_raw, volatile)]
+    pub fn update_weight(
+        sampler: &mut WeightedSampler,
+        index: INT,
+        weight: Dynamic,
+    ) -> Result<(), Box<EvalAltResult>> {
+        let len = sampler.weights.len();
+
+        if index < 0 || index as usize >= len {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "Invalid index (must be between 0 and {}): {}",
+                    len - 1,
+                    index
+                ),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        let weight = crate::weights::to_weight(&weight)?;
+
+        sampler.set(index as usize, weight);
+
+        Ok(())
+    }
+
+    /// Generate a random index from a weighted sampler, with a probability proportional to its
+    /// current weight.
+    /// Requires the `float` and `array` features.
+    ///
+    /// The weights of the sampler must not all be zero.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let sampler = weighted_sampler([5, 1, 1]);
+    ///
+    /// for i in 0..10 {
+    ///     print(sampler.draw());
+    /// }
+    /// ```
+    #[cfg(all(feature = "float", feature = "array"))]
+    #[rhai_fn(global, name = "draw", return_raw, volatile)]
+    pub fn draw_weighted(
+        ctx: NativeCallContext,
+        sampler: &mut WeightedSampler,
+    ) -> Result<INT, Box<EvalAltResult>> {
+        if sampler.weights.iter().all(|&w| w == 0.0) {
+            return Err(EvalAltResult::ErrorArithmetic(
+                "Weights must not all be zero".to_string(),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        // Guard against rounding errors accumulated by updates making the total non-positive
+        let total = sampler.total().max(FLOAT::MIN_POSITIVE);
+        let target = crate::rng::rng(&ctx).gen_range(0.0..total);
+
+        Ok(sampler.find(target) as INT)
+    }
+
+    /// Generate a random floating-point number following a Rayleigh distribution with the
+    /// specified scale `sigma`.
+    /// Requires the `float` feature.
+    ///
+    /// The result is the magnitude of a 2D vector whose components are independent normal
+    /// random numbers with a standard deviation of `sigma`, so it is never negative.
+    /// The mean of the distribution is `sigma * sqrt(π / 2)`.
+    ///
+    /// `sigma` must be positive.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let amplitude = rand_rayleigh(2.0);
+    ///
+    /// print(`Signal amplitude: ${amplitude}`);
+    /// ```
+    #[cfg(feature = "float")]
+    #[rhai_fn(return_raw, volatile)]
+    pub fn rand_rayleigh(
+        ctx: NativeCallContext,
+        sigma: FLOAT,
+    ) -> Result<FLOAT, Box<EvalAltResult>> {
+        if !(sigma > 0.0 && sigma.is_finite()) {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Invalid scale (must be positive): {}", sigma),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        let mut rng = crate::rng::rng(&ctx);
+        let x: FLOAT = rng.sample(StandardNormal);
+        let y: FLOAT = rng.sample(StandardNormal);
+
+        Ok(sigma * x.hypot(y))
+    }
+
+    /// Generate a random integer number following a negative binomial distribution,
+    /// i.e. the number of failures before the `r`-th success in a series of independent trials
+    /// that each succeed with probability `p`.
+    /// Requires the `float` feature.
+    ///
+    /// The result is never negative, and its mean is `r * (1 - p) / p`.
+    ///
+    /// `r` must be at least `1`, and `p` must be greater than `0.0` and at most `1.0`.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let misses = rand_negative_binomial(3, 0.4);
+    ///
+    /// print(`Missed ${misses} times before hitting the target three times`);
+    /// ```
+    #[cfg(feature = "float")]
+    #[rhai_fn(return_raw, volatile)]
+    pub fn rand_negative_binomial(
+        ctx: NativeCallContext,
+        r: INT,
+        p: FLOAT,
+    ) -> Result<INT, Box<EvalAltResult>> {
+        if r < 1 {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Invalid number of successes (must be at least 1): {}", r),
+                Position::NONE,
+            )
+            .into());
+        }
+        if !(p > 0.0 && p <= 1.0) {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "Invalid probability (must be greater than 0.0 and at most 1.0): {}",
+                    p
+                ),
+                Position::NONE,
+            )
+            .into());
+        }
+        if p == 1.0 {
+            return Ok(0);
+        }
+
+        let mut rng = crate::rng::rng(&ctx);
+
+        // A Poisson distribution whose rate follows a gamma distribution is negative binomial
+        let gamma = rand_distr::Gamma::new(r as FLOAT, (1.0 - p) / p).map_err(|err| {
+            EvalAltResult::ErrorArithmetic(
+                format!("Invalid negative binomial parameters: {}", err),
+                Position::NONE,
+            )
+        })?;
+        let rate = rng.sample(gamma);
+
+        if rate <= 0.0 {
+            return Ok(0);
+        }
+
+        let poisson = rand_distr::Poisson::new(rate).map_err(|err| {
+            EvalAltResult::ErrorArithmetic(
+                format!("Invalid negative binomial parameters: {}", err),
+                Position::NONE,
+            )
+        })?;
+
+        Ok(rng.sample(poisson) as INT)
+    }
+
+    /// Generate a random floating-point number between `0.0` and `1.0` (both exclusive)
+    /// following a Kumaraswamy distribution with shape parameters `a` and `b`.
+    /// Requires the `float` feature.
+    ///
+    /// The Kumaraswamy distribution is similar in shape to the Beta distribution, but much
+    /// cheaper to sample, as its inverse CDF has a closed form. Larger `a` moves values
+    /// towards `1.0`, while larger `b` moves them towards `0.0`.
+    ///
+    /// `a` and `b` must both be positive.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let completion = rand_kumaraswamy(2.0, 5.0);
+    ///
+    /// print(`Task is ${completion * 100.0}% complete`);
+    /// ```
+    #[cfg(feature = "float")]
+    #[rhai_fn(return_raw, volatile)]
+    pub fn rand_kumaraswamy(
+        ctx: NativeCallContext,
+        a: FLOAT,
+        b: FLOAT,
+    ) -> Result<FLOAT, Box<EvalAltResult>> {
+        if !(a > 0.0 && a.is_finite() && b > 0.0 && b.is_finite()) {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Invalid shape parameters (must be positive): {}, {}", a, b),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        let u: FLOAT = crate::rng::rng(&ctx).sample(rand_distr::Open01);
+        let x = (1.0 - (1.0 - u).powf(1.0 / b)).powf(1.0 / a);
+
+        // Extreme parameters may round the result onto the bounds
+        Ok(x.clamp(FLOAT::MIN_POSITIVE, 1.0 - FLOAT::EPSILON / 2.0))
+    }
+
+    /// Copy a random value from the observed `samples` that lies within `window` of the
+    /// quantile `q`, and return it.
+    /// Requires the `float` and `array` features.
+    ///
+    /// The samples are sorted, and a value is chosen uniformly among those whose quantile
+    /// (i.e. relative position in the sorted samples, from `0.0` to `1.0`) is between
+    /// `q - window` and `q + window`. This is useful for stress-testing with values typical
+    /// of a particular part of a distribution, such as its tails.
+    ///
+    /// `samples` must be a non-empty array of numbers, and `q` and `window` must both be
+    /// between `0.0` and `1.0` (inclusive).
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let latencies = [12, 15, 11, 90, 14, 13, 250, 16, 12, 14];
+    ///
+    /// let slow = sample_near_quantile(latencies, 0.95, 0.1);
+    ///
+    /// print(`A latency from the slowest requests: ${slow}`);
+    /// ```
+    #[cfg(all(feature = "float", feature = "array"))]
+    #[rhai_fn(return_raw, volatile)]
+    pub fn sample_near_quantile(
+        ctx: NativeCallContext,
+        samples: Array,
+        q: FLOAT,
+        window: FLOAT,
+    ) -> Result<FLOAT, Box<EvalAltResult>> {
+        if samples.is_empty() {
+            return Err(EvalAltResult::ErrorArithmetic(
+                "Samples must not be empty".to_string(),
+                Position::NONE,
+            )
+            .into());
+        }
+        if !(0.0..=1.0).contains(&q) {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Invalid quantile (must be between 0.0 and 1.0): {}", q),
+                Position::NONE,
+            )
+            .into());
+        }
+        if !(0.0..=1.0).contains(&window) {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Invalid window (must be between 0.0 and 1.0): {}", window),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        let mut sorted = samples
+            .iter()
+            .map(|v| super::to_float(v).filter(|v| !v.is_nan()))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| {
+                EvalAltResult::ErrorArithmetic(
+                    "Samples must be numbers".to_string(),
+                    Position::NONE,
+                )
+            })?;
+
+        sorted.sort_by(|a, b| a.total_cmp(b));
+
+        let last = (sorted.len() - 1) as FLOAT;
+        let start = ((q - window).max(0.0) * last).ceil() as usize;
+        let end = ((q + window).min(1.0) * last).floor() as usize;
+
+        // A narrow window may fall between two samples, so fall back to the nearest one
+        let index = if start <= end {
+            crate::rng::rng(&ctx).gen_range(start..=end)
+        } else {
+            (q * last).round() as usize
+        };
+
+        Ok(sorted[index])
+    }
+
+    /// Generate an array of `count` random integer numbers between `start` and `end`
+    /// (inclusive), where no number is the same as the one before it.
+    /// Requires the `array` feature.
+    ///
+    /// Each number is chosen uniformly among all the numbers in the range except the previous one.
+    ///
+    /// `count` must not be negative or exceed 1,048,576, and `start` must not be greater than
+    /// `end`. If `start` equals `end`, `count` must be at most `1`.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let notes = rand_sequence_distinct_adjacent(16, 1, 8);
+    ///
+    /// print(`A melody without repeated notes: ${notes}`);
+    /// ```
+    #[cfg(feature = "array")]
+    #[rhai_fn(return_raw, volatile)]
+    pub fn rand_sequence_distinct_adjacent(
+        ctx: NativeCallContext,
+        count: INT,
+        start: INT,
+        end: INT,
+    ) -> Result<Array, Box<EvalAltResult>> {
+        if count < 0 {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Invalid number of values (must not be negative): {}", count),
+                Position::NONE,
+            )
+            .into());
+        }
+        super::check_array_length(count)?;
+        if start > end {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Range is empty: {}..={}", start, end),
+                Position::NONE,
+            )
+            .into());
+        }
+        if start == end && count > 1 {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "Range must contain more than one number to avoid repeats: {}..={}",
+                    start, end
+                ),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        let mut rng = crate::rng::rng(&ctx);
+        let mut values = Array::with_capacity(count as usize);
+        let mut previous = None;
+
+        for _ in 0..count {
+            let value = match previous {
+                None => rng.gen_range(start..=end),
+                // Pick among the other numbers by skipping over the previous one
+                Some(previous) => {
+                    let value = rng.gen_range(start..end);
+
+                    if value >= previous {
+                        value + 1
+                    } else {
+                        value
+                    }
+                }
+            };
+
+            values.push(value.into());
+            previous = Some(value);
+        }
+
+        Ok(values)
+    }
+
+    /// Generate a random floating-point number by blending two of `values`, each picked with a
+    /// probability proportional to its weight in `weights`, at a random point between them.
+    /// Requires the `float` and `array` features.
+    ///
+    /// This models a soft random selection among presets: the result is usually close to the
+    /// heavily-weighted values, but can fall anywhere between them. It always lies between the
+    /// smallest and largest of `values` (inclusive).
+    ///
+    /// `values` must be a non-empty array of numbers, and `weights` must be an array of
+    /// non-negative numbers with the same length, which must not all be zero.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let presets = [0.2, 0.5, 0.9];     // e.g. calm, normal and aggressive
+    /// let weights = [1, 3, 1];
+    ///
+    /// let aggression = weighted_blend(presets, weights);
+    ///
+    /// print(`Aggression level: ${aggression}`);
+    /// ```
+    #[cfg(all(feature = "float", feature = "array"))]
+    #[rhai_fn(return_raw, volatile)]
+    pub fn weighted_blend(
+        ctx: NativeCallContext,
+        values: Array,
+        weights: Array,
+    ) -> Result<FLOAT, Box<EvalAltResult>> {
+        if values.is_empty() {
+            return Err(EvalAltResult::ErrorArithmetic(
+                "Values must not be empty".to_string(),
+                Position::NONE,
+            )
+            .into());
+        }
+        if weights.len() != values.len() {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "Number of weights ({}) does not match the number of values ({})",
+                    weights.len(),
+                    values.len()
+                ),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        let values = values
+            .iter()
+            .map(|v| super::to_float(v).filter(|v| v.is_finite()))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| {
+                EvalAltResult::ErrorArithmetic(
+                    "Values must be finite numbers".to_string(),
+                    Position::NONE,
+                )
+            })?;
+        let index = crate::weights::from_array(&weights)?;
+
+        let mut rng = crate::rng::rng(&ctx);
+        let a = values[rng.sample(&index)];
+        let b = values[rng.sample(&index)];
+        let t: FLOAT = rng.gen();
+
+        Ok(a + t * (b - a))
+    }
+
+    /// Generate an array of `n` random integer numbers clustered around `center`, following a
+    /// normal distribution with standard deviation `width`, rounded to the nearest integer and
+    /// clamped to between `min` and `max` (inclusive).
+    /// Requires the `float` and `array` features.
+    ///
+    /// A `width` of `0.0` places every number at `center` (clamped to the range).
+    ///
+    /// `n` must not be negative or exceed 1,048,576, `width` must not be negative, and `min` must
+    /// not be greater than `max`.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let positions = clustered_int_array(10, 50, 8.0, 0, 100);
+    ///
+    /// print(`Enemies placed at: ${positions}`);
+    /// ```
+    #[cfg(all(feature = "float", feature = "array"))]
+    #[rhai_fn(return_raw, volatile)]
+    pub fn clustered_int_array(
+        ctx: NativeCallContext,
+        n: INT,
+        center: INT,
+        width: FLOAT,
+        min: INT,
+        max: INT,
+    ) -> Result<Array, Box<EvalAltResult>> {
+        if n < 0 {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Invalid number of values (must not be negative): {}", n),
+                Position::NONE,
+            )
+            .into());
+        }
+        super::check_array_length(n)?;
+        if width < 0.0 || !width.is_finite() {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Invalid width (must not be negative): {}", width),
+                Position::NONE,
+            )
+            .into());
+        }
+        if min > max {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Range is empty: {}..={}", min, max),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        let mut rng = crate::rng::rng(&ctx);
+
+        Ok((0..n)
+            .map(|_| {
+                let z: FLOAT = rng.sample(StandardNormal);
+                let value = (center as FLOAT + width * z).round();
+
+                // Clamp in floating-point first, since casting saturates at the `INT` bounds
+                (value.clamp(min as FLOAT, max as FLOAT) as INT)
+                    .clamp(min, max)
+                    .into()
+            })
+            .collect())
+    }
+
+    /// Generate a random floating-point number by resampling the observed `samples` with
+    /// Gaussian kernel smoothing, i.e. picking a random sample and adding normal noise with
+    /// standard deviation `bandwidth`.
+    /// Requires the `float` and `array` features.
+    ///
+    /// This draws from a kernel density estimate of the distribution the samples came from,
+    /// which is useful for a smoothed bootstrap. A `bandwidth` of `0.0` returns one of the
+    /// samples unchanged.
+    ///
+    /// `samples` must be a non-empty array of numbers, and `bandwidth` must not be negative.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let heights = [1.62, 1.75, 1.80, 1.68, 1.71];
+    ///
+    /// let height = kde_sample(heights, 0.03);
+    ///
+    /// print(`A plausible height: ${height}`);
+    /// ```
+    #[cfg(all(feature = "float", feature = "array"))]
+    #[rhai_fn(return_raw, volatile)]
+    pub fn kde_sample(
+        ctx: NativeCallContext,
+        samples: Array,
+        bandwidth: FLOAT,
+    ) -> Result<FLOAT, Box<EvalAltResult>> {
+        if samples.is_empty() {
+            return Err(EvalAltResult::ErrorArithmetic(
+                "Samples must not be empty".to_string(),
+                Position::NONE,
+            )
+            .into());
+        }
+        if bandwidth < 0.0 || !bandwidth.is_finite() {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Invalid bandwidth (must not be negative): {}", bandwidth),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        let mut rng = crate::rng::rng(&ctx);
+        let sample = samples.choose(&mut rng).unwrap();
+        let value = super::to_float(sample).ok_or_else(|| {
+            EvalAltResult::ErrorArithmetic(
+                format!("Samples must be numbers: {}", sample),
+                Position::NONE,
+            )
+        })?;
+
+        if bandwidth == 0.0 {
+            return Ok(value);
+        }
+
+        let z: FLOAT = rng.sample(StandardNormal);
+
+        Ok(value + bandwidth * z)
+    }
+
+    /// Generate a random vector of floating-point numbers, where each component follows a
+    /// normal distribution with its own mean and standard deviation, truncated to its own
+    /// range `[lower, upper]`.
+    /// Requires the `float` and `array` features.
+    ///
+    /// Each component is generated independently, by inverting the cumulative distribution
+    /// function as `truncated_normal_array` does.
+    ///
+    /// `means`, `stds`, `lowers` and `uppers` must be arrays of numbers with the same length.
+    /// Every standard deviation must be positive, and every lower bound must be less than the
+    /// corresponding upper bound.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let params = truncated_normal_vector([0.5, 10.0], [0.2, 3.0], [0.0, 1.0], [1.0, 20.0]);
+    ///
+    /// print(`Learning rate: ${params[0]}, batch size: ${params[1]}`);
+    /// ```
+    #[cfg(all(feature = "float", feature = "array"))]
+    #[rhai_fn(return_raw, volatile)]
+    pub fn truncated_normal_vector(
+        ctx: NativeCallContext,
+        means: Array,
+        stds: Array,
+        lowers: Array,
+        uppers: Array,
+    ) -> Result<Array, Box<EvalAltResult>> {
+        let to_floats = |values: &Array, name: &str| {
+            values
+                .iter()
+                .map(|v| super::to_float(v).filter(|v| !v.is_nan()))
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(|| -> Box<EvalAltResult> {
+                    EvalAltResult::ErrorArithmetic(
+                        format!("{} must be numbers", name),
+                        Position::NONE,
+                    )
+                    .into()
+                })
+        };
+
+        let means = to_floats(&means, "Means")?;
+        let stds = to_floats(&stds, "Standard deviations")?;
+        let lowers = to_floats(&lowers, "Lower bounds")?;
+        let uppers = to_floats(&uppers, "Upper bounds")?;
+
+        if stds.len() != means.len() || lowers.len() != means.len() || uppers.len() != means.len() {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "Numbers of means ({}), standard deviations ({}), lower bounds ({}) and upper bounds ({}) do not match",
+                    means.len(),
+                    stds.len(),
+                    lowers.len(),
+                    uppers.len()
+                ),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        for i in 0..means.len() {
+            if !means[i].is_finite() {
+                return Err(EvalAltResult::ErrorArithmetic(
+                    format!("Invalid mean (must be finite): {}", means[i]),
+                    Position::NONE,
+                )
+                .into());
+            }
+            if !(stds[i] > 0.0 && stds[i].is_finite()) {
+                return Err(EvalAltResult::ErrorArithmetic(
+                    format!("Invalid standard deviation (must be positive): {}", stds[i]),
+                    Position::NONE,
+                )
+                .into());
+            }
+            if lowers[i] >= uppers[i] {
+                return Err(EvalAltResult::ErrorArithmetic(
+                    format!("Range is empty: {}..{}", lowers[i], uppers[i]),
+                    Position::NONE,
+                )
+                .into());
+            }
+        }
+
+        let mut rng = crate::rng::rng(&ctx);
+
+        Ok((0..means.len())
+            .map(|i| {
+                super::sample_truncated_normal(&mut rng, means[i], stds[i], lowers[i], uppers[i])
+                    .into()
+            })
+            .collect())
+    }
+
+    /// Generate an array of `n` random boolean values that are `true` with probability `p`,
+    /// grouped into runs of identical values with an average length of `avg_run`.
+    /// Requires the `float` and `array` features.
+    ///
+    /// The values follow a two-state Markov chain whose switching probabilities are derived
+    /// from `p` and `avg_run`, so the overall proportion of `true` values stays close to `p`
+    /// while the values come in bursts. An `avg_run` of `2.0` with `p = 0.5` gives independent
+    /// flips, while larger values give longer bursts.
+    ///
+    /// Runs can be no shorter than one value, so if `avg_run` is too short to be reached for
+    /// the given `p`, the values simply alternate as often as `p` allows.
+    ///
+    /// `n` must not be negative or exceed 1,048,576, `p` must be between `0.0` and `1.0`
+    /// (inclusive), and `avg_run` must be at least `1.0`.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let busy = bursty_flips(60, 0.2, 5.0);
+    ///
+    /// print(`Busy seconds in the last minute: ${busy}`);
+    /// ```
+    #[cfg(all(feature = "float", feature = "array"))]
+    #[rhai_fn(return_raw, volatile)]
+    #[allow(clippy::unnecessary_cast)]
+    pub fn bursty_flips(
+        ctx: NativeCallContext,
+        n: INT,
+        p: FLOAT,
+        avg_run: FLOAT,
+    ) -> Result<Array, Box<EvalAltResult>> {
+        if n < 0 {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Invalid number of values (must not be negative): {}", n),
+                Position::NONE,
+            )
+            .into());
+        }
+        super::check_array_length(n)?;
+        super::check_probability(p)?;
+        if !(avg_run >= 1.0 && avg_run.is_finite()) {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "Invalid average run length (must be at least 1.0): {}",
+                    avg_run
+                ),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        // Runs of `true` and `false` alternate with mean lengths `1 / leave_true` and
+        // `1 / leave_false`, which average to `avg_run`, while the stationary proportion of
+        // `true` values is `leave_false / (leave_true + leave_false) = p`.
+        let mut leave_true = 1.0 / (2.0 * avg_run * p);
+        let mut leave_false = 1.0 / (2.0 * avg_run * (1.0 - p));
+
+        if leave_true > 1.0 {
+            leave_true = 1.0;
+            leave_false = p / (1.0 - p);
+        } else if leave_false > 1.0 {
+            leave_false = 1.0;
+            leave_true = (1.0 - p) / p;
+        }
+
+        let mut rng = crate::rng::rng(&ctx);
+        let mut value = rng.gen_bool(p as f64);
+        let mut values = Array::with_capacity(n as usize);
+
+        for _ in 0..n {
+            values.push(value.into());
+
+            let leave = if value { leave_true } else { leave_false };
+
+            if rng.gen_bool(leave.clamp(0.0, 1.0) as f64) {
+                value = !value;
+            }
+        }
+
+        Ok(values)
+    }
+
+    /// Create a drawer that returns the elements of an array one at a time in random order,
+    /// without replacement.
+    /// Requires the `array` feature.
+    ///
+    /// Use `draw_next` on the returned drawer to draw the next element. Unlike shuffling, no work
+    /// is done upfront, so this is suitable for drawing a few elements from a huge array.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let drawer = make_drawer(["ace", "king", "queen", "jack"]);
+    ///
+    /// print(`First card: ${drawer.draw_next()}`);
+    /// print(`Second card: ${drawer.draw_next()}`);
+    /// ```
+    #[cfg(feature = "array")]
+    #[rhai_fn(volatile)]
+    pub fn make_drawer(array: Array) -> ArrayDrawer {
+        ArrayDrawer { remaining: array }
+    }
+
+    /// Draw a random element, which has not been drawn before, from a drawer.
+    /// Requires the `array` feature.
+    ///
+    /// Each element of the original array is returned exactly once.
+    /// `()` is returned when all elements have been drawn.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let drawer = make_drawer([1, 2, 3, 4, 5]);
+    ///
+    /// loop {
+    ///     let x = drawer.draw_next();
+    ///
+    ///     if x == () { break; }
+    ///
+    ///     print(x);
+    /// }
+    /// ```
+    #[cfg(feature = "array")]
+    #[rhai_fn(global, volatile)]
+    pub fn draw_next(ctx: NativeCallContext, drawer: &mut ArrayDrawer) -> Dynamic {
+        let remaining = &mut drawer.remaining;
+
+        if remaining.is_empty() {
+            return Dynamic::UNIT;
+        }
+
+        let index = crate::rng::rng(&ctx).gen_range(0..remaining.len());
+
+        remaining.swap_remove(index)
+    }
+
+    /// Generate an array of `n` random integer numbers between `start` and `end` (inclusive),
+    /// in non-decreasing order.
+    /// Requires the `array` feature.
+    ///
+    /// `n` must not be negative or exceed 1,048,576, and `start` must not be greater than `end`.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let timestamps = rand_monotonic(5, 1000, 2000);
+    ///
+    /// print(`Sorted random timestamps: ${timestamps}`);
+    /// ```
+    #[cfg(feature = "array")]
+    #[rhai_fn(return_raw, volatile)]
+    pub fn rand_monotonic(
+        ctx: NativeCallContext,
+        n: INT,
+        start: INT,
+        end: INT,
+    ) -> Result<Array, Box<EvalAltResult>> {
+        if n < 0 {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Invalid number of values (must not be negative): {}", n),
+                Position::NONE,
+            )
+            .into());
+        }
+        super::check_array_length(n)?;
+        if start > end {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Range is empty: {}..={}", start, end),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        let mut rng = crate::rng::rng(&ctx);
+        let mut values = (0..n)
+            .map(|_| rng.gen_range(start..=end))
+            .collect::<Vec<_>>();
+
+        values.sort_unstable();
+
+        Ok(values.into_iter().map(Dynamic::from).collect())
+    }
+
+    /// Select a random element from `array`, with a probability proportional to its
+    /// corresponding weight in `weights`, deterministically from `seed`.
+    /// Requires the `float` and `array` features.
+    ///
+    /// Unlike other functions, the result for the same inputs is guaranteed never to change
+    /// between versions of this package, so it is suitable for reproducible results that must
+    /// survive upgrades. This does not affect the sequence of values generated by other
+    /// functions. The algorithm is fixed as follows:
+    ///
+    /// 1. Normalize the weights: divide each weight by the largest weight, then by the sum of the
+    ///    results, so that even huge weights do not overflow. If the largest weight is too small
+    ///    to divide by reliably, all positive weights are treated as equal instead.
+    /// 2. Seed a [ChaCha8](https://docs.rs/rand_chacha/0.3) generator with `seed`, via
+    ///    `SeedableRng::seed_from_u64`.
+    /// 3. Take the top 53 bits of its first `u64` output as a fraction `u` in `[0, 1)`.
+    /// 4. Multiply `u` by the sum of all normalized weights (added in order, as 64-bit
+    ///    floating-point numbers) to get a target.
+    /// 5. Return the first element whose running total of normalized weights exceeds the target
+    ///    (or the last element with a positive weight, should rounding leave none).
+    ///
+    /// `()` is returned if the array is empty.
+    ///
+    /// `weights` must be an array of non-negative numbers with the same length as `array`,
+    /// which must not all be zero (unless the array is empty).
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let item = weighted_sample_stable(["sword", "shield", "potion"], [1, 2, 7], 42);
+    ///
+    /// print(`Always the same item for seed 42: ${item}`);
+    /// ```
+    #[cfg(all(feature = "float", feature = "array"))]
+    #[rhai_fn(return_raw)]
+    #[allow(clippy::unnecessary_cast)]
+    pub fn weighted_sample_stable(
+        array: Array,
+        weights: Array,
+        seed: INT,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        if weights.len() != array.len() {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "Number of weights ({}) does not match the number of elements ({})",
+                    weights.len(),
+                    array.len()
+                ),
+                Position::NONE,
+            )
+            .into());
+        }
+        if array.is_empty() {
+            return Ok(Dynamic::UNIT);
+        }
+
+        let weights = weights
+            .iter()
+            .map(crate::weights::to_weight)
+            .collect::<Result<Vec<_>, _>>()?;
+        let weights: Vec<f64> = crate::weights::normalize(&weights)?
+            .into_iter()
+            .map(|w| w as f64)
+            .collect();
+        let total: f64 = weights.iter().sum();
+
+        let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(seed as u64);
+        let u = (rng.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+        let target = u * total;
+
+        let mut cumulative = 0.0;
+
+        for (value, &weight) in array.iter().zip(&weights) {
+            cumulative += weight;
+
+            if cumulative > target {
+                return Ok(value.clone());
+            }
+        }
+
+        let last = weights
+            .iter()
+            .rposition(|&w| w > 0.0)
+            .expect("total is positive");
+
+        Ok(array[last].clone())
+    }
+
+    /// Generate an array of `n` random integer numbers between `start` and `end` (inclusive),
+    /// whose mean is close to `target_mean`.
+    /// Requires the `float` and `array` features.
+    ///
+    /// Values are drawn from a beta distribution stretched over the range and shifted so that
+    /// its mean is `target_mean`, then rounded to the nearest integer. A `target_mean` in the
+    /// middle of the range produces uniformly distributed values, while a `target_mean` at
+    /// either end of the range produces only that value.
+    ///
+    /// `n` must not be negative or exceed 1,048,576, `start` must not be greater than `end`, and
+    /// `target_mean` must be between `start` and `end` (inclusive).
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let scores = rand_array_mean(20, 0, 100, 75.0);
+    ///
+    /// print(`Test scores averaging around 75: ${scores}`);
+    /// ```
+    #[cfg(all(feature = "float", feature = "array"))]
+    #[rhai_fn(return_raw, volatile)]
+    pub fn rand_array_mean(
+        ctx: NativeCallContext,
+        n: INT,
+        start: INT,
+        end: INT,
+        target_mean: FLOAT,
+    ) -> Result<Array, Box<EvalAltResult>> {
+        if n < 0 {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Invalid number of values (must not be negative): {}", n),
+                Position::NONE,
+            )
+            .into());
+        }
+        super::check_array_length(n)?;
+        if start > end {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Range is empty: {}..={}", start, end),
+                Position::NONE,
+            )
+            .into());
+        }
+        if !(start as FLOAT..=end as FLOAT).contains(&target_mean) {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "Invalid target mean (must be between {} and {}): {}",
+                    start, end, target_mean
+                ),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        let width = end as FLOAT - start as FLOAT;
+        let fraction = if width > 0.0 {
+            (target_mean - start as FLOAT) / width
+        } else {
+            0.0
+        };
+
+        if fraction <= 0.0 || fraction >= 1.0 {
+            let value = if fraction >= 1.0 { end } else { start };
+            return Ok(vec![value.into(); n as usize]);
+        }
+
+        // A total concentration of 2 makes a target in the middle of the range uniform
+        let beta =
+            rand_distr::Beta::new(2.0 * fraction, 2.0 * (1.0 - fraction)).map_err(|err| {
+                EvalAltResult::ErrorArithmetic(
+                    format!("Invalid beta distribution parameters: {}", err),
+                    Position::NONE,
+                )
+            })?;
+        let mut rng = crate::rng::rng(&ctx);
+
+        Ok((0..n)
+            .map(|_| {
+                let x: FLOAT = rng.sample(beta);
+                let value = (start as FLOAT + x * width).round();
+
+                // Clamp in floating-point first, since casting saturates at the `INT` bounds
+                (value.clamp(start as FLOAT, end as FLOAT) as INT)
+                    .clamp(start, end)
+                    .into()
+            })
+            .collect())
+    }
+
+    /// Generate a random sample of a 1D Gaussian process with zero mean at each of the points in
+    /// `x_points`, returning an array of values in the same order.
+    /// Requires the `float` and `array` features.
+    ///
+    /// The values are correlated by a squared exponential (RBF) kernel, i.e. the covariance
+    /// between the values at points `a` and `b` is `variance * exp(-(a - b)² / (2 * length_scale²))`.
+    /// Points closer together than `length_scale` therefore have similar values, giving smooth
+    /// random functions.
+    ///
+    /// `x_points` must be an array of at most 2,000 numbers in ascending order, and
+    /// `length_scale` and `variance` must be positive.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let x = [0.0, 0.5, 1.0, 1.5, 2.0, 2.5, 3.0];
+    ///
+    /// let y = gp_sample(x, 1.0, 4.0);
+    ///
+    /// print(`A smooth random function: ${y}`);
+    /// ```
+    #[cfg(all(feature = "float", feature = "array"))]
+    #[rhai_fn(return_raw, volatile)]
+    pub fn gp_sample(
+        ctx: NativeCallContext,
+        x_points: Array,
+        length_scale: FLOAT,
+        variance: FLOAT,
+    ) -> Result<Array, Box<EvalAltResult>> {
+        /// Maximum number of points, since the covariance matrix takes `O(n²)` memory and its
+        /// factorization `O(n³)` time.
+        const MAX_POINTS: usize = 2000;
+
+        if x_points.len() > MAX_POINTS {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "Too many points (maximum {}): {}",
+                    MAX_POINTS,
+                    x_points.len()
+                ),
+                Position::NONE,
+            )
+            .into());
+        }
+        if !(length_scale > 0.0 && length_scale.is_finite()) {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Invalid length scale (must be positive): {}", length_scale),
+                Position::NONE,
+            )
+            .into());
+        }
+        if !(variance > 0.0 && variance.is_finite()) {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Invalid variance (must be positive): {}", variance),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        let x = x_points
+            .iter()
+            .map(|v| super::to_float(v).filter(|v| v.is_finite()))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| {
+                EvalAltResult::ErrorArithmetic(
+                    "Points must be finite numbers".to_string(),
+                    Position::NONE,
+                )
+            })?;
+
+        if x.windows(2).any(|w| w[0] > w[1]) {
+            return Err(EvalAltResult::ErrorArithmetic(
+                "Points must be in ascending order".to_string(),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        let covariance: Vec<Vec<FLOAT>> = x
+            .iter()
+            .map(|&a| {
+                x.iter()
+                    .map(|&b| {
+                        let d = (a - b) / length_scale;
+                        variance * (-0.5 * d * d).exp()
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let lower = super::cholesky(&covariance);
+        let mut rng = crate::rng::rng(&ctx);
+        let z: Vec<FLOAT> = (0..x.len()).map(|_| rng.sample(StandardNormal)).collect();
+
+        Ok(lower
+            .iter()
+            .map(|row| row.iter().zip(&z).map(|(l, z)| l * z).sum::<FLOAT>().into())
+            .collect())
+    }
+
+    /// Roll for loot from rarity tiers: pick a tier with a probability proportional to its
+    /// chance, then return a random item from that tier.
+    /// Requires the `float` and `array` features.
+    ///
+    /// Each tier in `tiers` must be an object map with a `chance` field containing a non-negative
+    /// number, and an `items` field containing an array of items. The chances do not need to sum
+    /// to `1.0`, but must not all be zero. Tiers with a zero chance are never picked, so their
+    /// `items` may be empty.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let tiers = [
+    ///     #{ chance: 0.80, items: ["stick", "rock", "rope"] },
+    ///     #{ chance: 0.15, items: ["sword", "shield"] },
+    ///     #{ chance: 0.05, items: ["dragon scale"] }
+    /// ];
+    ///
+    /// print(`You found: ${roll_loot(tiers)}`);
+    /// ```
+    #[cfg(all(feature = "float", feature = "array"))]
+    #[rhai_fn(return_raw, volatile)]
+    pub fn roll_loot(ctx: NativeCallContext, tiers: Array) -> Result<Dynamic, Box<EvalAltResult>> {
+        if tiers.is_empty() {
+            return Err(EvalAltResult::ErrorArithmetic(
+                "Tiers must not be empty".to_string(),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        let tiers = tiers
+            .iter()
+            .enumerate()
+            .map(|(i, tier)| {
+                let tier_error = |message: &str| {
+                    EvalAltResult::ErrorArithmetic(
+                        format!("Tier {} {}", i, message),
+                        Position::NONE,
+                    )
+                };
+
+                let tier = tier
+                    .read_lock::<rhai::Map>()
+                    .ok_or_else(|| tier_error("is not an object map"))?;
+                let chance = tier
+                    .get("chance")
+                    .ok_or_else(|| tier_error("has no 'chance' field"))?;
+                let chance = crate::weights::to_weight(chance)?;
+                let items = tier
+                    .get("items")
+                    .and_then(|items| items.read_lock::<Array>().map(|items| items.clone()))
+                    .ok_or_else(|| tier_error("has no 'items' array"))?;
+
+                if chance > 0.0 && items.is_empty() {
+                    return Err(tier_error("has no items").into());
+                }
+
+                Ok((chance, items))
+            })
+            .collect::<Result<Vec<_>, Box<EvalAltResult>>>()?;
+
+        let chances: Vec<FLOAT> = tiers.iter().map(|(chance, _)| *chance).collect();
+        let index = crate::weights::from_values(&chances)?;
+        let mut rng = crate::rng::rng(&ctx);
+        let items = &tiers[rng.sample(&index)].1;
+
+        Ok(items[rng.gen_range(0..items.len())].clone())
+    }
+
+    /// Generate an array of `n` random integer numbers.
+    /// Requires the `array` feature.
+    ///
+    /// An empty array is returned if `n` is zero or negative.
+    /// `n` must not exceed 1,048,576.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let numbers = rand_array(10);
+    ///
+    /// print(`I'll give you 10 random numbers: ${numbers}`);
+    /// ```
+    #[cfg(feature = "array")]
+    #[rhai_fn(return_raw, volatile)]
+    pub fn rand_array(ctx: NativeCallContext, n: INT) -> Result<Array, Box<EvalAltResult>> {
+        super::check_array_length(n)?;
+
+        let mut rng = crate::rng::rng(&ctx);
+
+        Ok((0..n).map(|_| rng.gen::<INT>().into()).collect())
+    }
+
+    /// Generate an array of `n` random integer numbers, each within an exclusive range.
+    /// Requires the `array` feature.
+    ///
+    /// An empty array is returned if `n` is zero or negative.
+    /// `n` must not exceed 1,048,576, and `range` must not be empty.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let rolls = rand_array(10, 1..7);
+    ///
+    /// print(`I'll give you 10 dice rolls: ${rolls}`);
+    /// ```
+    #[cfg(feature = "array")]
+    #[rhai_fn(name = "rand_array", return_raw, volatile)]
+    pub fn rand_array_range(
+        ctx: NativeCallContext,
+        n: INT,
+        range: Range<INT>,
+    ) -> Result<Array, Box<EvalAltResult>> {
+        super::check_array_length(n)?;
+
+        if range.is_empty() {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Range is empty: {:?}", range),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        let mut rng = crate::rng::rng(&ctx);
+
+        Ok((0..n)
+            .map(|_| rng.gen_range(range.clone()).into())
+            .collect())
+    }
+
+    /// Generate an array of `n` random integer numbers between `start` and `end` (inclusive),
+    /// spread evenly but with random jitter, in ascending order.
+    /// Requires the `array` feature.
+    ///
+    /// The range is divided into `n` consecutive cells of (nearly) equal size, and one random
+    /// number is picked within each cell.
+    ///
+    /// `n` must be at least `1`, and the range must contain at least `n` numbers.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let trees = rand_jittered_grid(5, 0, 99);
+    ///
+    /// print(`Trees planted along the road at: ${trees}`);
+    /// ```
+    #[cfg(feature = "array")]
+    #[rhai_fn(return_raw, volatile)]
+    pub fn rand_jittered_grid(
+        ctx: NativeCallContext,
+        n: INT,
+        start: INT,
+        end: INT,
+    ) -> Result<Array, Box<EvalAltResult>> {
+        if n < 1 {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Invalid number of values (must be at least 1): {}", n),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        // Computed in `i128` so that the size of the full `INT` range does not overflow
+        let size = end as i128 - start as i128 + 1;
+
+        if size < n as i128 {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Range {}..={} is too small for {} values", start, end, n),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        let mut rng = crate::rng::rng(&ctx);
+        let cell_start = |i: i128| start as i128 + i * size / n as i128;
+
+        Ok((0..n as i128)
+            .map(|i| (rng.gen_range(cell_start(i)..cell_start(i + 1)) as INT).into())
+            .collect())
+    }
+
+    /// Generate a random boolean value with a probability of being `true` that adapts to a
+    /// `history` of recent outcomes, raising it after failures and lowering it after successes.
+    /// Requires the `float` and `array` features.
+    ///
+    /// The probability is `base_p + sensitivity * (failures - successes) / len`, clamped to
+    /// between `0.0` and `1.0`, where `failures` and `successes` are the number of `false` and
+    /// `true` values in `history` and `len` is its length. An empty `history` uses `base_p`.
+    ///
+    /// `history` must be an array of booleans, `base_p` must be between `0.0` and `1.0`
+    /// (inclusive), and `sensitivity` must not be negative.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let history = [false, false, true, false];
+    ///
+    /// let hit = adaptive_roll(history, 0.5, 0.4);     // more likely after a losing streak
+    ///
+    /// history.push(hit);
+    /// ```
+    #[cfg(all(feature = "float", feature = "array"))]
+    #[rhai_fn(return_raw, volatile)]
+    #[allow(clippy::unnecessary_cast)]
+    pub fn adaptive_roll(
+        ctx: NativeCallContext,
+        history: Array,
+        base_p: FLOAT,
+        sensitivity: FLOAT,
+    ) -> Result<bool, Box<EvalAltResult>> {
+        super::check_probability(base_p)?;
+
+        if !(sensitivity >= 0.0 && sensitivity.is_finite()) {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "Invalid sensitivity (must not be negative): {}",
+                    sensitivity
+                ),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        let history = history
+            .iter()
+            .map(|v| v.as_bool().ok())
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| {
+                EvalAltResult::ErrorArithmetic(
+                    "History must be an array of booleans".to_string(),
+                    Position::NONE,
+                )
+            })?;
+
+        let p = if history.is_empty() {
+            base_p
+        } else {
+            let successes = history.iter().filter(|&&success| success).count();
+            let failures = history.len() - successes;
+            let balance = (failures as FLOAT - successes as FLOAT) / history.len() as FLOAT;
+
+            (base_p + sensitivity * balance).clamp(0.0, 1.0)
+        };
+
+        Ok(crate::rng::rng(&ctx).gen_bool(p as f64))
+    }
+
+    /// Create a shuffler with its own random number generator seeded with `seed`.
+    /// Requires the `array` feature.
+    ///
+    /// Use `shuffle_with` on the returned shuffler to shuffle arrays. Each shuffle advances the
+    /// shuffler's generator, so shufflers created with the same `seed` produce the same orders
+    /// for the same sequence of arrays. This does not affect the sequence of values generated by
+    /// other functions.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let shuffler = make_shuffler(42);
+    ///
+    /// let round1 = shuffler.shuffle_with([1, 2, 3, 4, 5]);
+    /// let round2 = shuffler.shuffle_with([1, 2, 3, 4, 5]);   // a different order
+    /// ```
+    #[cfg(feature = "array")]
+    pub fn make_shuffler(seed: INT) -> Shuffler {
+        Shuffler {
+            rng: rand::rngs::StdRng::seed_from_u64(seed as u64),
+        }
+    }
+
+    /// Return a shuffled copy of `array`, using the shuffler's random number generator.
+    /// Requires the `array` feature.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let shuffler = make_shuffler(42);
+    ///
+    /// for round in 0..3 {
+    ///     print(shuffler.shuffle_with(["a", "b", "c", "d"]));
+    /// }
+    /// ```
+    #[cfg(feature = "array")]
+    #[rhai_fn(global)]
+    pub fn shuffle_with(shuffler: &mut Shuffler, mut array: Array) -> Array {
+        array.shuffle(&mut shuffler.rng);
+        array
+    }
+
+    /// Generate a random integer number across orders of magnitude: first pick a bucket `k`
+    /// between `0` and `buckets - 1` (inclusive) with a probability proportional to `base ** -k`,
+    /// then pick a random number between `base ** k` and `base ** (k + 1)`, rounded down.
+    /// Requires the `float` feature.
+    ///
+    /// Each bucket is `base` times less likely than the one before it, so small magnitudes are
+    /// the most common but large ones still occur.
+    ///
+    /// `buckets` must be at least `1`, `base` must be greater than `1.0`, and `base ** buckets`
+    /// must fit in an integer.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let file_size = rand_exp_bucketed(6, 10.0);     // between 1 and 999,999 bytes
+    ///
+    /// print(`A random file size: ${file_size} bytes`);
+    /// ```
+    #[cfg(feature = "float")]
+    #[rhai_fn(return_raw, volatile)]
+    pub fn rand_exp_bucketed(
+        ctx: NativeCallContext,
+        buckets: INT,
+        base: FLOAT,
+    ) -> Result<INT, Box<EvalAltResult>> {
+        if buckets < 1 {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "Invalid number of buckets (must be at least 1): {}",
+                    buckets
+                ),
+                Position::NONE,
+            )
+            .into());
+        }
+        if !(base > 1.0 && base.is_finite()) {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Invalid base (must be greater than 1.0): {}", base),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        let max = base.powf(buckets as FLOAT);
+
+        if max >= INT::MAX as FLOAT {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Bucket range overflows: {} ** {}", base, buckets),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        let mut rng = crate::rng::rng(&ctx);
+
+        // Inverse transform of the truncated geometric distribution of bucket indices
+        let ratio = 1.0 / base;
+        let u: FLOAT = rng.gen();
+        let tail = 1.0 - u * (1.0 - ratio.powf(buckets as FLOAT));
+        let k = ((tail.ln() / ratio.ln()).floor() as INT).clamp(0, buckets - 1);
+
+        let low = base.powf(k as FLOAT);
+
+        Ok(rng.gen_range(low..low * base).floor() as INT)
+    }
+
+    /// Generate a random unit 3D vector uniformly distributed within a cone of directions, i.e.
+    /// at most `max_angle` radians away from the direction of `axis`.
+    /// Requires the `float` and `array` features.
+    ///
+    /// Directions are sampled uniformly over the spherical cap of the cone, so a `max_angle` of
+    /// `π` gives uniformly distributed directions over the whole sphere. The returned vector is an
+    /// `[x, y, z]` array.
+    ///
+    /// `axis` must be a non-zero `[x, y, z]` vector, which does not need to be of unit length,
+    /// and `max_angle` must be between `0.0` and `π` (inclusive).
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let spark = rand_in_cone([0.0, 1.0, 0.0], 0.3);     // mostly upwards
+    ///
+    /// print(`A spark flies off in direction ${spark}`);
+    /// ```
+    #[cfg(all(feature = "float", feature = "array"))]
+    #[rhai_fn(return_raw, volatile)]
+    pub fn rand_in_cone(
+        ctx: NativeCallContext,
+        axis: Array,
+        max_angle: FLOAT,
+    ) -> Result<Array, Box<EvalAltResult>> {
+        let axis = super::to_vector(&axis.into(), 3).ok_or_else(|| {
+            EvalAltResult::ErrorArithmetic(
+                "Axis must be an [x, y, z] vector of numbers".to_string(),
+                Position::NONE,
+            )
+        })?;
+        let length = axis.iter().map(|v| v * v).sum::<FLOAT>().sqrt();
+
+        if !(length > 0.0 && length.is_finite()) {
+            return Err(EvalAltResult::ErrorArithmetic(
+                "Axis must be a non-zero vector".to_string(),
+                Position::NONE,
+            )
+            .into());
+        }
+        if !(0.0..=PI).contains(&max_angle) {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!(
+                    "Invalid angle (must be between 0.0 and {}): {}",
+                    PI, max_angle
+                ),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        let w = [axis[0] / length, axis[1] / length, axis[2] / length];
+
+        // Build an orthonormal basis `(u, v, w)` around the axis, starting from whichever
+        // coordinate axis is least aligned with it
+        let helper = if w[0].abs() < 0.9 {
+            [1.0, 0.0, 0.0]
+        } else {
+            [0.0, 1.0, 0.0]
+        };
+        let cross = |a: [FLOAT; 3], b: [FLOAT; 3]| {
+            [
+                a[1] * b[2] - a[2] * b[1],
+                a[2] * b[0] - a[0] * b[2],
+                a[0] * b[1] - a[1] * b[0],
+            ]
+        };
+        let u = cross(helper, w);
+        let u_length = u.iter().map(|v| v * v).sum::<FLOAT>().sqrt();
+        let u = [u[0] / u_length, u[1] / u_length, u[2] / u_length];
+        let v = cross(w, u);
+
+        // The height of a uniform point on a spherical cap is itself uniform
+        let mut rng = crate::rng::rng(&ctx);
+        let cos_theta = 1.0 - rng.gen_range(0.0..=1.0) * (1.0 - max_angle.cos());
+        let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        let phi = rng.gen_range(0.0..2.0 * PI);
+        let (a, b) = (sin_theta * phi.cos(), sin_theta * phi.sin());
+
+        Ok((0..3)
+            .map(|i| (a * u[i] + b * v[i] + cos_theta * w[i]).into())
+            .collect())
+    }
+
+    /// Select a random index of `logits`, each chosen with a probability proportional to
+    /// `exp(logit / temperature)`, i.e. sample from the softmax of the logits.
+    /// Requires the `float` and `array` features.
+    ///
+    /// Higher `temperature` values flatten the distribution, while lower values favor the
+    /// highest logits more strongly.
+    ///
+    /// `logits` must be a non-empty array of finite numbers, and `temperature` must be positive.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let logits = [2.0, 1.0, 0.1];
+    ///
+    /// let token = softmax_sample(logits, 0.7);
+    ///
+    /// print(`Selected token ${token}, most likely 0`);
+    /// ```
+    #[cfg(all(feature = "float", feature = "array"))]
+    #[rhai_fn(return_raw, volatile)]
+    pub fn softmax_sample(
+        ctx: NativeCallContext,
+        logits: Array,
+        temperature: FLOAT,
+    ) -> Result<INT, Box<EvalAltResult>> {
+        if logits.is_empty() {
+            return Err(EvalAltResult::ErrorArithmetic(
+                "Logits must not be empty".to_string(),
+                Position::NONE,
+            )
+            .into());
+        }
+        if !(temperature > 0.0 && temperature.is_finite()) {
+            return Err(EvalAltResult::ErrorArithmetic(
+                format!("Invalid temperature (must be positive): {}", temperature),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        let logits = logits
+            .iter()
+            .map(|v| super::to_float(v).filter(|v| v.is_finite()))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| {
+                EvalAltResult::ErrorArithmetic(
+                    "Logits must be finite numbers".to_string(),
+                    Position::NONE,
+                )
+            })?;
+
+        // Subtract the largest logit so that the largest weight is exactly `1.0` and
+        // `exp` cannot overflow
+        let max = logits.iter().copied().fold(FLOAT::NEG_INFINITY, FLOAT::max);
+        let weights: Vec<FLOAT> = logits
+            .iter()
+            .map(|&logit| ((logit - max) / temperature).exp())
+            .collect();
+        let index = crate::weights::from_values(&weights)?;
+
+        Ok(crate::rng::rng(&ctx).sample(&index) as INT)
     }
 }
@@ -0,0 +1,142 @@
+//! The random number generators used by all functions in the package.
+//!
+//! Every `RandomPackage` owns a generator slot, which holds either a seeded [`StdRng`] or nothing,
+//! in which case random numbers come from [`rand::thread_rng`] and are seeded non-deterministically.
+//!
+//! The slot is registered under the id of the package's module when the package is created
+//! (see [`install`]), and functions find the slot of the package they were called from through
+//! [`NativeCallContext::fn_source`]. This way, seeding one package (via `RandomPackage::with_seed`
+//! or the `rand_seed` script function) never affects the values generated by another package.
+//!
+//! Functions called without a registered package (e.g. when the package's functions have been
+//! copied into another module) fall back to a slot for the current thread.
+
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+use rhai::{Dynamic, Module, NativeCallContext};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError, Weak};
+
+/// Generator slot of a package. `None` means that the package is not seeded.
+type Slot = Arc<Mutex<Option<StdRng>>>;
+
+/// Name of the module variable that keeps the generator slot of a package alive.
+///
+/// Variables of global modules are not visible to scripts, and this name is not a valid
+/// identifier anyway.
+const SLOT_VAR: &str = "$rng";
+
+/// Slots of all live packages, by module id.
+static SLOTS: Mutex<BTreeMap<String, Weak<Mutex<Option<StdRng>>>>> = Mutex::new(BTreeMap::new());
+
+/// Counter for generating unique module ids.
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    /// Seeded generator for functions called without a registered package, if one has been
+    /// installed on the current thread.
+    static FALLBACK: RefCell<Option<StdRng>> = const { RefCell::new(None) };
+}
+
+/// Give `module` a unique id and an unseeded generator slot of its own.
+///
+/// The slot is stored in `module` itself, so it lives exactly as long as the module.
+pub fn install(module: &mut Module) {
+    let id = format!("rhai-rand#{}", NEXT_ID.fetch_add(1, Ordering::Relaxed));
+    let slot = Slot::default();
+
+    let mut slots = SLOTS.lock().unwrap_or_else(PoisonError::into_inner);
+    slots.retain(|_, slot| slot.strong_count() > 0);
+    slots.insert(id.clone(), Arc::downgrade(&slot));
+    drop(slots);
+
+    module.set_id(id);
+    module.set_var(SLOT_VAR, Dynamic::from(slot));
+}
+
+/// Find the generator slot registered under a module id.
+fn find_slot(id: Option<&str>) -> Option<Slot> {
+    let slots = SLOTS.lock().unwrap_or_else(PoisonError::into_inner);
+
+    slots.get(id?)?.upgrade()
+}
+
+/// Replace the generator in a slot, or in the fallback slot of the current thread if there is
+/// no slot.
+fn set(slot: Option<Slot>, rng: Option<StdRng>) {
+    match slot {
+        Some(slot) => *slot.lock().unwrap_or_else(PoisonError::into_inner) = rng,
+        None => FALLBACK.with(|fallback| *fallback.borrow_mut() = rng),
+    }
+}
+
+/// Install a generator seeded with `seed` for the package that `module` belongs to.
+pub fn seed_module(module: &Module, seed: u64) {
+    set(find_slot(module.id()), Some(StdRng::seed_from_u64(seed)));
+}
+
+/// Install a generator seeded with `seed` for the package of the calling function.
+pub fn seed(ctx: &NativeCallContext, seed: u64) {
+    set(
+        find_slot(ctx.fn_source()),
+        Some(StdRng::seed_from_u64(seed)),
+    );
+}
+
+/// Remove the seeded generator of the package of the calling function, if any, so that random
+/// numbers come from [`rand::thread_rng`] again.
+pub fn clear_seed(ctx: &NativeCallContext) {
+    set(find_slot(ctx.fn_source()), None);
+}
+
+/// Handle to the random number generator of a package.
+///
+/// The underlying generator is only locked for the duration of each call, so a handle can be
+/// kept across calls to script callbacks that themselves generate random numbers.
+#[derive(Debug, Clone)]
+pub struct PackageRng {
+    /// Generator slot of the package, if the function was called from a registered package.
+    slot: Option<Slot>,
+}
+
+/// Get a handle to the random number generator of the package of the calling function.
+pub fn rng(ctx: &NativeCallContext) -> PackageRng {
+    PackageRng {
+        slot: find_slot(ctx.fn_source()),
+    }
+}
+
+impl PackageRng {
+    fn with_rng<T>(&self, f: impl FnOnce(&mut dyn RngCore) -> T) -> T {
+        match &self.slot {
+            Some(slot) => match slot.lock().unwrap_or_else(PoisonError::into_inner).as_mut() {
+                Some(rng) => f(rng),
+                None => f(&mut rand::thread_rng()),
+            },
+            None => FALLBACK.with(|fallback| match fallback.borrow_mut().as_mut() {
+                Some(rng) => f(rng),
+                None => f(&mut rand::thread_rng()),
+            }),
+        }
+    }
+}
+
+impl RngCore for PackageRng {
+    fn next_u32(&mut self) -> u32 {
+        self.with_rng(|rng| rng.next_u32())
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.with_rng(|rng| rng.next_u64())
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.with_rng(|rng| rng.fill_bytes(dest))
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.with_rng(|rng| rng.try_fill_bytes(dest))
+    }
+}
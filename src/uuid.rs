@@ -0,0 +1,84 @@
+#[allow(unused_imports)]
+use rhai::plugin::*;
+
+use std::sync::Mutex;
+
+/// Number of random bits in a version 7 UUID.
+const RANDOM_BITS: u32 = 74;
+
+/// Timestamp (in milliseconds) and random bits of the most recently generated version 7 UUID.
+static LAST_UUID_V7: Mutex<(u64, u128)> = Mutex::new((0, 0));
+
+/// Assemble a version 7 UUID from a 48-bit millisecond timestamp and 74 random bits.
+fn uuid_v7(timestamp: u64, random: u128) -> u128 {
+    let timestamp = (timestamp & ((1 << 48) - 1)) as u128;
+    let rand_a = random >> 62;
+    let rand_b = random & ((1 << 62) - 1);
+
+    (timestamp << 80) | (0x7 << 76) | (rand_a << 64) | (0b10 << 62) | rand_b
+}
+
+/// Format a UUID as a lowercase hyphenated hex string.
+fn to_hyphenated(uuid: u128) -> String {
+    let hex = format!("{:032x}", uuid);
+
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
+
+#[export_module]
+pub mod uuid_functions {
+    use rand::prelude::*;
+    use rhai::ImmutableString;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    /// Generate a random version 7 UUID, as a lowercase hyphenated hex string.
+    /// Requires the `uuid` feature.
+    ///
+    /// Version 7 UUIDs start with a millisecond timestamp followed by random bits, so UUIDs
+    /// generated later always sort after earlier ones, both as strings and as numbers.
+    /// UUIDs generated within the same millisecond are kept in order by incrementing the random
+    /// bits of the previous one by a random amount.
+    ///
+    /// ### Example
+    ///
+    /// ```rhai
+    /// let id = rand_uuid_v7();
+    ///
+    /// print(`New record ID: ${id}`);     // e.g. 01890a5d-ac96-774b-bcce-b302099a8057
+    /// ```
+    #[rhai_fn(volatile)]
+    pub fn rand_uuid_v7(ctx: NativeCallContext) -> ImmutableString {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis() as u64);
+        let mut rng = crate::rng::rng(&ctx);
+        let mut last = super::LAST_UUID_V7
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+
+        let (last_timestamp, last_random) = *last;
+        let random = rng.gen::<u128>() >> (128 - super::RANDOM_BITS);
+
+        *last = if now > last_timestamp {
+            (now, random)
+        } else {
+            let next = last_random + rng.gen_range(1..=1 << 32);
+
+            // Move on to the next millisecond when the random bits run out
+            if next >> super::RANDOM_BITS == 0 {
+                (last_timestamp, next)
+            } else {
+                (last_timestamp + 1, random)
+            }
+        };
+
+        super::to_hyphenated(super::uuid_v7(last.0, last.1)).into()
+    }
+}
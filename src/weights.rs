@@ -0,0 +1,83 @@
+//! Shared handling of weights for the weighted sampling functions.
+//!
+//! All weighted samplers go through [`normalize`] (usually via [`from_array`] or [`from_values`])
+//! so that invalid weights are reported consistently, and so that weights too small to be
+//! normalized reliably do not produce `NaN` probabilities.
+//!
+//! Samplers that take weights in another form convert them first: `sample_with_cdf` takes the
+//! differences between its cumulative weights, and `weighted_sample_stable` converts the
+//! normalized weights to 64-bit numbers for its fixed algorithm.
+//!
+//! The one exception is `weighted_sampler`, whose weights are updated one at a time and so are
+//! only checked with [`to_weight`].
+
+use rand::distributions::WeightedIndex;
+use rhai::{Array, Dynamic, EvalAltResult, Position, FLOAT};
+
+fn weights_error(message: String) -> Box<EvalAltResult> {
+    EvalAltResult::ErrorArithmetic(message, Position::NONE).into()
+}
+
+/// Convert a single weight into a `FLOAT`, checking that it is a non-negative finite number.
+pub fn to_weight(value: &Dynamic) -> Result<FLOAT, Box<EvalAltResult>> {
+    match crate::convert::to_float(value) {
+        Some(weight) if weight >= 0.0 && weight.is_finite() => Ok(weight),
+        _ => Err(weights_error(format!(
+            "Weights must be non-negative numbers: {}",
+            value
+        ))),
+    }
+}
+
+/// Normalize weights into probabilities that sum to `1.0`.
+///
+/// Weights must be non-negative finite numbers, and must not all be zero.
+///
+/// Weights are first scaled by the largest weight, so very large or very small weights do not
+/// overflow or underflow when summed. If even the largest weight is too small to be scaled
+/// reliably (i.e. it is a subnormal number), all positive weights are treated as equal and the
+/// selection falls back to being uniform among them.
+pub fn normalize(weights: &[FLOAT]) -> Result<Vec<FLOAT>, Box<EvalAltResult>> {
+    if let Some(&weight) = weights.iter().find(|w| !w.is_finite() || **w < 0.0) {
+        return Err(weights_error(format!(
+            "Weights must be non-negative numbers: {}",
+            weight
+        )));
+    }
+
+    let max = weights.iter().copied().fold(0.0, FLOAT::max);
+
+    if max <= 0.0 {
+        return Err(weights_error("Weights must not all be zero".to_string()));
+    }
+
+    let scaled: Vec<FLOAT> = if max < FLOAT::MIN_POSITIVE {
+        weights
+            .iter()
+            .map(|&w| if w > 0.0 { 1.0 } else { 0.0 })
+            .collect()
+    } else {
+        weights.iter().map(|&w| w / max).collect()
+    };
+
+    let total: FLOAT = scaled.iter().sum();
+
+    Ok(scaled.into_iter().map(|w| w / total).collect())
+}
+
+/// Create a [`WeightedIndex`] from a list of weights, normalizing them with [`normalize`].
+pub fn from_values(weights: &[FLOAT]) -> Result<WeightedIndex<FLOAT>, Box<EvalAltResult>> {
+    WeightedIndex::new(normalize(weights)?)
+        .map_err(|err| weights_error(format!("Invalid weights: {}", err)))
+}
+
+/// Create a [`WeightedIndex`] from an array of numeric weights, normalizing them with
+/// [`normalize`].
+pub fn from_array(weights: &Array) -> Result<WeightedIndex<FLOAT>, Box<EvalAltResult>> {
+    let weights = weights
+        .iter()
+        .map(to_weight)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    from_values(&weights)
+}
@@ -0,0 +1,123 @@
+#![cfg(feature = "array")]
+
+use rhai::{packages::Package, Blob, Engine, EvalAltResult, INT};
+use rhai_rand::RandomPackage;
+
+#[test]
+fn test_blob_sample() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    for _ in 0..100 {
+        let byte = engine.eval::<INT>(
+            "
+                let b = blob();
+                b += 3; b += 5; b += 7;
+                b.sample()
+            ",
+        )?;
+
+        assert!([3, 5, 7].contains(&byte), "Not in the BLOB: {}", byte);
+    }
+
+    assert_eq!(
+        engine.eval::<()>("blob().sample()")?,
+        (),
+        "Should handle empty BLOB's"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_blob_sample2() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let blob = engine.eval::<Blob>(
+        "
+            let b = blob(16);
+            for i in 0..16 { b[i] = i; }
+            b.sample(5)
+        ",
+    )?;
+
+    assert_eq!(
+        blob.len(),
+        5,
+        "Should return a BLOB matching the requested sample size"
+    );
+
+    let mut sorted = blob.clone();
+    sorted.sort_unstable();
+    sorted.dedup();
+
+    assert_eq!(sorted.len(), 5, "Should not return any duplicate samples");
+    assert!(sorted.iter().all(|&byte| byte < 16));
+
+    let mut blob = engine.eval::<Blob>(
+        "
+            let b = blob(16);
+            for i in 0..16 { b[i] = i; }
+            b.sample(100)
+        ",
+    )?;
+    blob.sort_unstable();
+
+    assert_eq!(
+        blob,
+        (0..16).collect::<Blob>(),
+        "Should return the entire BLOB when the sample size is too large"
+    );
+
+    assert!(engine.eval::<Blob>("blob(10).sample(0)")?.is_empty());
+    assert!(engine.eval::<Blob>("blob(10).sample(-1)")?.is_empty());
+    assert!(engine.eval::<Blob>("blob().sample(3)")?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_blob_shuffle() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let blob = engine.eval::<Blob>(
+        "
+            let b = blob();
+            b.shuffle();
+            b
+        ",
+    )?;
+
+    assert!(blob.is_empty(), "Should not affect empty BLOB's");
+
+    let blob = engine.eval::<Blob>(
+        "
+            let b = blob(256);
+            for i in 0..256 { b[i] = i; }
+            b.shuffle();
+            b
+        ",
+    )?;
+
+    assert!(
+        blob.iter().enumerate().any(|(i, &byte)| byte as usize != i),
+        "BLOB should be shuffled: {:?}",
+        blob
+    );
+
+    let mut sorted = blob.clone();
+    sorted.sort_unstable();
+
+    assert_eq!(
+        sorted,
+        (0..=255).collect::<Blob>(),
+        "Bytes should not change"
+    );
+
+    Ok(())
+}
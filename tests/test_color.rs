@@ -0,0 +1,143 @@
+#![cfg(feature = "float")]
+
+use rhai::{packages::Package, Engine, EvalAltResult};
+use rhai_rand::RandomPackage;
+
+fn parse_hex(color: &str) -> (u8, u8, u8) {
+    assert_eq!(color.len(), 7, "Invalid color: {}", color);
+    assert!(color.starts_with('#'), "Invalid color: {}", color);
+
+    let channel = |i: usize| u8::from_str_radix(&color[i..i + 2], 16).unwrap();
+
+    (channel(1), channel(3), channel(5))
+}
+
+#[test]
+fn test_rand_color_temperature() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    for _ in 0..100 {
+        let (r, _, b) =
+            parse_hex(&engine.eval::<String>("rand_color_temperature(1500.0, 2500.0)")?);
+        assert!(r > b, "Low temperatures should be warm: {} vs {}", r, b);
+
+        let (r, _, b) =
+            parse_hex(&engine.eval::<String>("rand_color_temperature(10000.0, 20000.0)")?);
+        assert!(b > r, "High temperatures should be cool: {} vs {}", r, b);
+    }
+
+    assert_eq!(
+        parse_hex(&engine.eval::<String>("rand_color_temperature(6600.0, 6600.0)")?),
+        (255, 255, 255),
+        "6600K should be close to white"
+    );
+
+    assert!(engine
+        .eval::<String>("rand_color_temperature(100.0, 2000.0)")
+        .is_err());
+    assert!(engine
+        .eval::<String>("rand_color_temperature(3000.0, 2000.0)")
+        .is_err());
+
+    Ok(())
+}
+
+/// Hue of an RGB color, in degrees.
+#[cfg(feature = "array")]
+fn hue((r, g, b): (u8, u8, u8)) -> f64 {
+    let (r, g, b) = (r as f64 / 255.0, g as f64 / 255.0, b as f64 / 255.0);
+    let max = r.max(g).max(b);
+    let delta = max - r.min(g).min(b);
+
+    if max == r {
+        60.0 * ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / delta + 2.0)
+    } else {
+        60.0 * ((r - g) / delta + 4.0)
+    }
+}
+
+#[cfg(feature = "array")]
+#[test]
+fn test_analogous_colors() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    // Red sits at hue 0, so hues on both sides of the wrap-around are checked
+    for (base, base_hue) in [("#3366CC", 220.0), ("#E61A1A", 0.0)] {
+        let colors =
+            engine.eval::<rhai::Array>(&format!(r#"analogous_colors("{}", 50, 20.0)"#, base))?;
+
+        assert_eq!(colors.len(), 50);
+
+        for color in colors {
+            let color = parse_hex(&color.into_string().unwrap());
+            let distance = (hue(color) - base_hue).rem_euclid(360.0);
+            let distance = distance.min(360.0 - distance);
+
+            // Allow for rounding of the channels
+            assert!(distance <= 21.0, "Hue outside the spread: {:?}", color);
+        }
+    }
+
+    assert!(engine
+        .eval::<rhai::Array>(r##"analogous_colors("#3366CC", 0, 20.0)"##)?
+        .is_empty());
+    assert!(engine
+        .eval::<rhai::Array>(r#"analogous_colors("3366CC", 3, 20.0)"#)
+        .is_err());
+    assert!(engine
+        .eval::<rhai::Array>(r##"analogous_colors("#3366CG", 3, 20.0)"##)
+        .is_err());
+    assert!(engine
+        .eval::<rhai::Array>(r##"analogous_colors("#3366CC", 3, 181.0)"##)
+        .is_err());
+    assert!(engine
+        .eval::<rhai::Array>(r##"analogous_colors("#3366CC", -1, 20.0)"##)
+        .is_err());
+    assert!(engine
+        .eval::<rhai::Array>(r##"analogous_colors("#3366CC", 1 << 40, 10.0)"##)
+        .is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_mix_colors() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let mut colors = std::collections::HashSet::new();
+
+    for _ in 0..100 {
+        let color = engine.eval::<String>(r##"mix_colors("#FF2080", "#10A0ff")"##)?;
+        let (r, g, b) = parse_hex(&color);
+
+        assert!((0x10..=0xFF).contains(&r), "Red out of range: {}", color);
+        assert!((0x20..=0xA0).contains(&g), "Green out of range: {}", color);
+        assert!((0x80..=0xFF).contains(&b), "Blue out of range: {}", color);
+
+        colors.insert(color);
+    }
+
+    assert!(colors.len() > 10, "Blends should vary");
+
+    assert_eq!(
+        engine.eval::<String>(r##"mix_colors("#123456", "#123456")"##)?,
+        "#123456"
+    );
+
+    assert!(engine
+        .eval::<String>(r##"mix_colors("#123456", "red")"##)
+        .is_err());
+    assert!(engine
+        .eval::<String>(r##"mix_colors("#12345", "#123456")"##)
+        .is_err());
+
+    Ok(())
+}
@@ -0,0 +1,56 @@
+use rhai::{packages::Package, Engine, EvalAltResult, INT};
+use rhai_rand::RandomPackage;
+
+#[test]
+fn test_map_sample() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let mut seen = Vec::new();
+
+    for _ in 0..100 {
+        let key = engine.eval::<String>("#{ a: 1, b: 2, c: 3 }.sample()")?;
+
+        assert!(
+            ["a", "b", "c"].contains(&key.as_str()),
+            "Unknown key: {}",
+            key
+        );
+
+        if !seen.contains(&key) {
+            seen.push(key);
+        }
+    }
+
+    assert_eq!(seen.len(), 3, "Every key should be returned: {:?}", seen);
+
+    assert_eq!(
+        engine.eval::<()>("#{}.sample()")?,
+        (),
+        "Should handle empty object maps"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_map_sample_value() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    for _ in 0..100 {
+        let value = engine.eval::<INT>("#{ a: 1, b: 2, c: 3 }.sample_value()")?;
+
+        assert!((1..=3).contains(&value), "Unknown value: {}", value);
+    }
+
+    assert_eq!(
+        engine.eval::<()>("#{}.sample_value()")?,
+        (),
+        "Should handle empty object maps"
+    );
+
+    Ok(())
+}
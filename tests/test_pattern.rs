@@ -0,0 +1,83 @@
+#![cfg(feature = "pattern")]
+
+use rhai::{packages::Package, Engine, EvalAltResult};
+use rhai_rand::RandomPackage;
+
+#[test]
+fn test_rand_pattern() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    for _ in 0..100 {
+        let plate = engine.eval::<String>(r#"rand_pattern("[A-Z]{3}-[0-9]{4}")"#)?;
+        let chars: Vec<_> = plate.chars().collect();
+
+        assert_eq!(chars.len(), 8, "Wrong length: {}", plate);
+        assert!(
+            chars[..3].iter().all(|c| c.is_ascii_uppercase()),
+            "Bad plate: {}",
+            plate
+        );
+        assert_eq!(chars[3], '-', "Bad plate: {}", plate);
+        assert!(
+            chars[4..].iter().all(|c| c.is_ascii_digit()),
+            "Bad plate: {}",
+            plate
+        );
+
+        let phone =
+            engine.eval::<String>(r#"rand_pattern("\\([2-9][0-9]{2}\\) [0-9]{3}-[0-9]{4}")"#)?;
+        let chars: Vec<_> = phone.chars().collect();
+
+        assert_eq!(chars.len(), 14, "Wrong length: {}", phone);
+        assert_eq!(chars[0], '(', "Bad phone number: {}", phone);
+        assert!(
+            ('2'..='9').contains(&chars[1]),
+            "Bad phone number: {}",
+            phone
+        );
+        assert_eq!(&phone[4..6], ") ", "Bad phone number: {}", phone);
+        assert_eq!(chars[9], '-', "Bad phone number: {}", phone);
+        assert!(
+            [1, 2, 3, 6, 7, 8, 10, 11, 12, 13]
+                .iter()
+                .all(|&i| chars[i].is_ascii_digit()),
+            "Bad phone number: {}",
+            phone
+        );
+
+        let hex = engine.eval::<String>(r##"rand_pattern("#[0-9a-f]{6}")"##)?;
+
+        assert_eq!(hex.len(), 7, "Wrong length: {}", hex);
+        assert!(
+            hex[1..].chars().all(|c| c.is_ascii_hexdigit()),
+            "Bad hex: {}",
+            hex
+        );
+    }
+
+    assert_eq!(engine.eval::<String>(r#"rand_pattern("abc")"#)?, "abc");
+    assert_eq!(engine.eval::<String>(r#"rand_pattern("x{3}")"#)?, "xxx");
+
+    for pattern in [
+        "a+",
+        "[abc",
+        "(a|b)",
+        "{3}",
+        "a{2,3}",
+        "[z-a]",
+        "[^a]",
+        "a{18446744073709551615}b{18446744073709551615}",
+    ] {
+        assert!(
+            engine
+                .eval::<String>(&format!("rand_pattern({:?})", pattern))
+                .is_err(),
+            "Pattern should be rejected: {}",
+            pattern
+        );
+    }
+
+    Ok(())
+}
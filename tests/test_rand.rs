@@ -1,4 +1,6 @@
-use rhai::{packages::Package, Engine, EvalAltResult, INT};
+#![allow(clippy::bool_assert_comparison, clippy::expect_fun_call)]
+
+use rhai::{packages::Package, Engine, EvalAltResult, OptimizationLevel, INT};
 use rhai_rand::RandomPackage;
 
 #[cfg(feature = "float")]
@@ -207,3 +209,4140 @@ fn test_shuffle() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[cfg(all(feature = "float", feature = "array"))]
+#[test]
+fn test_stochastic_top_k() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let mut counts = [0; 5];
+
+    for _ in 0..1000 {
+        let picks = engine.eval::<Array>(
+            "
+                let x = [0, 1, 2, 3, 4];
+                x.stochastic_top_k([0.0, 1.0, 2.0, 3.0, 4.0], 2, 1.0)
+            ",
+        )?;
+
+        assert_eq!(
+            picks.len(),
+            2,
+            "Should return the requested number of elements"
+        );
+
+        let picks: Vec<_> = picks.into_iter().map(|v| v.as_int().unwrap()).collect();
+
+        assert_ne!(picks[0], picks[1], "Should not return duplicates");
+
+        for n in picks {
+            counts[n as usize] += 1;
+        }
+    }
+
+    assert!(
+        counts[4] > counts[2] && counts[2] > counts[0],
+        "Higher-scored elements should be selected more often: {:?}",
+        counts
+    );
+
+    assert!(engine
+        .eval::<Array>("[1, 2, 3].stochastic_top_k([1, 2], 1, 1.0)")
+        .is_err());
+    assert!(engine
+        .eval::<Array>("[1, 2, 3].stochastic_top_k([1, 2, 3], 4, 1.0)")
+        .is_err());
+    assert!(engine
+        .eval::<Array>("[1, 2, 3].stochastic_top_k([1, 2, 3], 1, 0.0)")
+        .is_err());
+    assert!(engine
+        .eval::<Array>("[1, 2, 3].stochastic_top_k([1, 2, 3], 1, 0.0 / 0.0)")
+        .is_err());
+    assert!(engine
+        .eval::<Array>("[1, 2, 3].stochastic_top_k([1, 0.0 / 0.0, 3], 1, 1.0)")
+        .is_err());
+
+    Ok(())
+}
+
+#[cfg(feature = "float")]
+#[test]
+fn test_rand_von_mises() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let spread = |kappa: FLOAT| -> Result<(FLOAT, FLOAT), Box<EvalAltResult>> {
+        let (mut sin, mut cos) = (0.0, 0.0);
+        let mut deviation = 0.0;
+
+        for _ in 0..2000 {
+            let angle = engine.eval::<FLOAT>(&format!("rand_von_mises(1.0, {:?})", kappa))?;
+
+            assert!(
+                angle > -std::f64::consts::PI && angle <= std::f64::consts::PI,
+                "Angle should be in (-PI, PI]: {}",
+                angle
+            );
+
+            sin += angle.sin();
+            cos += angle.cos();
+            deviation += (angle - 1.0).abs();
+        }
+
+        Ok((sin.atan2(cos), deviation / 2000.0))
+    };
+
+    let (mean, loose) = spread(2.0)?;
+    assert!(
+        (mean - 1.0).abs() < 0.1,
+        "Circular mean should be near 1.0: {}",
+        mean
+    );
+
+    let (mean, tight) = spread(50.0)?;
+    assert!(
+        (mean - 1.0).abs() < 0.05,
+        "Circular mean should be near 1.0: {}",
+        mean
+    );
+
+    assert!(
+        tight < loose,
+        "Higher concentration should cluster angles: {} vs {}",
+        tight,
+        loose
+    );
+
+    let _ = spread(0.0)?;
+
+    assert!(engine.eval::<FLOAT>("rand_von_mises(0.0, -1.0)").is_err());
+
+    Ok(())
+}
+
+#[cfg(all(feature = "float", feature = "array"))]
+#[test]
+fn test_markov_bool() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let states = engine.eval::<Array>(
+        "
+            let state = true;
+            let states = [];
+
+            for i in 0..20000 {
+                state = markov_bool(state, 0.95, 0.9);
+                states.push(state);
+            }
+
+            states
+        ",
+    )?;
+
+    let states: Vec<_> = states.into_iter().map(|v| v.as_bool().unwrap()).collect();
+
+    let runs = 1 + states.windows(2).filter(|w| w[0] != w[1]).count();
+    let average_run = states.len() as FLOAT / runs as FLOAT;
+    assert!(average_run > 5.0, "Runs should be long: {}", average_run);
+
+    // Stationary probability of `true` = (1 - 0.9) / ((1 - 0.95) + (1 - 0.9)) = 2/3
+    let ratio = states.iter().filter(|&&v| v).count() as FLOAT / states.len() as FLOAT;
+    assert!(
+        (ratio - 2.0 / 3.0).abs() < 0.05,
+        "True ratio should be near 2/3: {}",
+        ratio
+    );
+
+    assert!(!engine.eval::<bool>("markov_bool(true, 0.0, 1.0)")?);
+    assert!(!engine.eval::<bool>("markov_bool(false, 0.0, 1.0)")?);
+    assert!(engine.eval::<bool>("markov_bool(true, 1.5, 0.5)").is_err());
+
+    Ok(())
+}
+
+#[cfg(all(feature = "float", feature = "array"))]
+#[test]
+fn test_rand_in_polygon() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    for _ in 0..100 {
+        // Diamond with vertices on the axes: |x| + |y| <= 2
+        let point =
+            engine.eval::<Array>("rand_in_polygon([[2, 0], [0, 2], [-2.0, 0], [0, -2.0]])")?;
+
+        assert_eq!(point.len(), 2);
+
+        let x = point[0].as_float().unwrap();
+        let y = point[1].as_float().unwrap();
+
+        assert!(
+            x.abs() + y.abs() <= 2.0,
+            "Point should be inside: ({}, {})",
+            x,
+            y
+        );
+    }
+
+    assert!(engine
+        .eval::<Array>("rand_in_polygon([[0, 0], [1, 1]])")
+        .is_err());
+    assert!(engine
+        .eval::<Array>("rand_in_polygon([[0, 0], [1, 1], [2, 2]])")
+        .is_err());
+    assert!(engine
+        .eval::<Array>("rand_in_polygon([[0, 0], [1, 1], 42])")
+        .is_err());
+    assert!(engine
+        .eval::<Array>("rand_in_polygon([[0, 0], [1.0 / 0.0, 0], [0, 1]])")
+        .is_err());
+    assert!(engine
+        .eval::<Array>("rand_in_polygon([[-1e308, 0], [1e308, 0], [0, 1]])")
+        .is_err());
+
+    Ok(())
+}
+
+#[cfg(all(feature = "float", feature = "array"))]
+#[test]
+fn test_sample_with_cdf() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let samples = engine.eval::<Array>(
+        "
+            let x = [0, 1, 2, 3];
+            let cumulative = [1, 1, 4.0, 10];   // weights of 1, 0, 3, 6
+            let samples = [];
+
+            for i in 0..10000 {
+                samples.push(x.sample_with_cdf(cumulative));
+            }
+
+            samples
+        ",
+    )?;
+
+    let mut counts = [0; 4];
+
+    for n in samples {
+        counts[n.as_int().unwrap() as usize] += 1;
+    }
+
+    assert_eq!(
+        counts[1], 0,
+        "Zero-weight elements should never be selected"
+    );
+
+    for (n, weight) in [(0, 0.1), (2, 0.3), (3, 0.6)] {
+        let ratio = counts[n] as FLOAT / 10000.0;
+        assert!(
+            (ratio - weight).abs() < 0.03,
+            "Element {} should be selected about {} of the time: {}",
+            n,
+            weight,
+            ratio
+        );
+    }
+
+    assert!(engine.eval::<()>("[].sample_with_cdf([])").is_ok());
+    assert!(engine
+        .eval::<rhai::Dynamic>("[1, 2].sample_with_cdf([1])")
+        .is_err());
+    assert!(engine
+        .eval::<rhai::Dynamic>("[1, 2].sample_with_cdf([2, 1])")
+        .is_err());
+    assert!(engine
+        .eval::<rhai::Dynamic>("[1, 2].sample_with_cdf([1.0, 1e308 * 10.0])")
+        .is_err());
+
+    Ok(())
+}
+
+#[cfg(all(feature = "float", feature = "array"))]
+#[test]
+fn test_loaded_die() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let mut counts = [0; 6];
+
+    for _ in 0..5000 {
+        let roll = engine.eval::<INT>("loaded_die(6, [1, 1, 1, 1, 1, 5])")?;
+
+        assert!((1..=6).contains(&roll), "Roll out of range: {}", roll);
+
+        counts[roll as usize - 1] += 1;
+    }
+
+    let ratio = counts[5] as FLOAT / 5000.0;
+    assert!(
+        (ratio - 0.5).abs() < 0.05,
+        "Six should come up half of the time: {}",
+        ratio
+    );
+
+    for &count in &counts[..5] {
+        assert!(
+            count < counts[5] / 2,
+            "Unweighted faces should be rarer: {:?}",
+            counts
+        );
+    }
+
+    assert!(engine.eval::<INT>("loaded_die(3, [1, 1])").is_err());
+    assert!(engine.eval::<INT>("loaded_die(2, [1, -1])").is_err());
+    assert!(engine.eval::<INT>("loaded_die(2, [0, 0])").is_err());
+
+    Ok(())
+}
+
+#[cfg(feature = "float")]
+#[test]
+fn test_sample_inverse() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    // Exponential distribution with rate 2: mean 0.5, median ln(2)/2
+    let mean = engine.eval::<FLOAT>(
+        "
+            let total = 0.0;
+
+            for i in 0..5000 {
+                let x = sample_inverse(|u| -(1.0 - u).ln() / 2.0);
+
+                if x < 0.0 { throw `negative sample: ${x}`; }
+
+                total += x;
+            }
+
+            total / 5000.0
+        ",
+    )?;
+
+    assert!(
+        (mean - 0.5).abs() < 0.05,
+        "Mean should be near 0.5: {}",
+        mean
+    );
+
+    let below = engine.eval::<INT>(
+        "
+            let count = 0;
+
+            for i in 0..5000 {
+                if sample_inverse(|u| -(1.0 - u).ln() / 2.0) < 2.0.ln() / 2.0 { count += 1; }
+            }
+
+            count
+        ",
+    )?;
+
+    assert!(
+        (below - 2500).abs() < 250,
+        "Half of the samples should be below the median: {}",
+        below
+    );
+
+    assert!(engine
+        .eval::<FLOAT>(r#"sample_inverse(|u| "hello")"#)
+        .is_err());
+
+    Ok(())
+}
+
+#[cfg(all(feature = "float", feature = "array"))]
+#[test]
+fn test_weighted_counts() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let counts = engine.eval::<Array>("weighted_counts([1, 0, 3.0, 6], 10000)")?;
+    let counts: Vec<_> = counts.into_iter().map(|v| v.as_int().unwrap()).collect();
+
+    assert_eq!(counts.len(), 4, "Should return one count per category");
+    assert_eq!(counts.iter().sum::<INT>(), 10000, "Counts should sum to n");
+    assert_eq!(counts[1], 0, "Zero-weight categories should never be drawn");
+
+    for (n, weight) in [(0, 0.1), (2, 0.3), (3, 0.6)] {
+        let ratio = counts[n] as FLOAT / 10000.0;
+        assert!(
+            (ratio - weight).abs() < 0.03,
+            "Category {} should be drawn about {} of the time: {}",
+            n,
+            weight,
+            ratio
+        );
+    }
+
+    let counts = engine.eval::<Array>("weighted_counts([1, 2], 0)")?;
+    assert!(counts.iter().all(|v| v.as_int().unwrap() == 0));
+
+    assert!(engine.eval::<Array>("weighted_counts([1, 2], -1)").is_err());
+    assert!(engine
+        .eval::<Array>("weighted_counts([1, -2], 10)")
+        .is_err());
+
+    Ok(())
+}
+
+#[cfg(feature = "float")]
+#[test]
+fn test_halton() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let expected: [(INT, INT, FLOAT); 10] = [
+        (0, 2, 0.0),
+        (1, 2, 0.5),
+        (2, 2, 0.25),
+        (3, 2, 0.75),
+        (4, 2, 0.125),
+        (5, 2, 0.625),
+        (1, 3, 1.0 / 3.0),
+        (2, 3, 2.0 / 3.0),
+        (3, 3, 1.0 / 9.0),
+        (4, 3, 4.0 / 9.0),
+    ];
+
+    for (index, base, value) in expected {
+        let result = engine.eval::<FLOAT>(&format!("halton({}, {})", index, base))?;
+
+        assert!(
+            (result - value).abs() < 1e-12,
+            "halton({}, {}) should be {}: {}",
+            index,
+            base,
+            value,
+            result
+        );
+    }
+
+    assert!(engine.eval::<FLOAT>("halton(-1, 2)").is_err());
+    assert!(engine.eval::<FLOAT>("halton(1, 1)").is_err());
+
+    Ok(())
+}
+
+#[cfg(all(feature = "float", feature = "array"))]
+#[test]
+fn test_quasi_point_2d() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    // Squared deviation of the 10x10 grid cell counts from a perfectly even spread
+    let unevenness = |points: Array| -> FLOAT {
+        let mut cells = [0.0; 100];
+
+        for point in points {
+            let point = point.into_typed_array::<FLOAT>().unwrap();
+
+            assert!((0.0..1.0).contains(&point[0]) && (0.0..1.0).contains(&point[1]));
+
+            cells[(point[0] * 10.0) as usize * 10 + (point[1] * 10.0) as usize] += 1.0;
+        }
+
+        cells.iter().map(|&n| (n - 10.0) * (n - 10.0)).sum()
+    };
+
+    let quasi = unevenness(engine.eval::<Array>(
+        "
+            let points = [];
+            for i in 1..=1000 { points.push(quasi_point_2d(i)); }
+            points
+        ",
+    )?);
+
+    let random = unevenness(engine.eval::<Array>(
+        "
+            let points = [];
+            for i in 1..=1000 { points.push([rand_float(), rand_float()]); }
+            points
+        ",
+    )?);
+
+    assert!(
+        quasi < random,
+        "Quasirandom points should be more even: {} vs {}",
+        quasi,
+        random
+    );
+
+    let point = engine.eval::<Array>("quasi_point_2d(1)")?;
+    assert_eq!(point.len(), 2);
+    assert_eq!(point[0].as_float().unwrap(), 0.5);
+
+    assert!(engine.eval::<Array>("quasi_point_2d(-1)").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_rand_int_where() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    for _ in 0..100 {
+        let value = engine.eval::<INT>("rand_int_where(|x| x % 2 == 0, 1, 100, 1000)")?;
+
+        assert!((1..=100).contains(&value), "Value out of range: {}", value);
+        assert_eq!(value % 2, 0, "Value should be even: {}", value);
+    }
+
+    assert!(engine
+        .eval::<()>("rand_int_where(|x| x > 100, 1, 100, 10)")
+        .is_ok());
+
+    assert!(engine
+        .eval::<INT>("rand_int_where(|x| true, 10, 1, 10)")
+        .is_err());
+    assert!(engine
+        .eval::<INT>("rand_int_where(|x| true, 1, 10, 0)")
+        .is_err());
+
+    Ok(())
+}
+
+#[cfg(feature = "array")]
+#[test]
+fn test_sample_avoid_recent() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    for _ in 0..100 {
+        let value = engine.eval::<INT>("[1, 2, 3, 4, 5].sample_avoid_recent([1, 3, 4])")?;
+
+        assert!(
+            value == 2 || value == 5,
+            "Should avoid recent elements: {}",
+            value
+        );
+
+        let value = engine.eval::<INT>("[1, 2, 3].sample_avoid_recent([1, 2, 3, 'x'])")?;
+
+        assert!(
+            (1..=3).contains(&value),
+            "Should fall back to all elements: {}",
+            value
+        );
+    }
+
+    assert!(engine.eval::<()>("[].sample_avoid_recent([1, 2])").is_ok());
+
+    Ok(())
+}
+
+#[cfg(all(feature = "float", feature = "array"))]
+#[test]
+fn test_empirical_sampler() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let draws = engine.eval::<Array>(
+        "
+            let sampler = empirical_sampler([5, 1.0, 2.0, 3.0, 4.0, 10.0]);
+            let draws = [];
+
+            for i in 0..5000 {
+                draws.push(sampler.draw());
+            }
+
+            draws
+        ",
+    )?;
+
+    let draws: Vec<_> = draws.into_iter().map(|v| v.as_float().unwrap()).collect();
+
+    assert!(
+        draws.iter().all(|&x| (1.0..=10.0).contains(&x)),
+        "Draws should stay within the observed range"
+    );
+
+    // The interpolated distribution spends half of its mass between 1 and 3.5
+    let below = draws.iter().filter(|&&x| x < 3.5).count() as FLOAT / 5000.0;
+    assert!(
+        (below - 0.5).abs() < 0.05,
+        "Half of the draws should be below 3.5: {}",
+        below
+    );
+
+    // The last interval (5..10) holds a fifth of the mass
+    let above = draws.iter().filter(|&&x| x > 5.0).count() as FLOAT / 5000.0;
+    assert!(
+        (above - 0.2).abs() < 0.05,
+        "A fifth of the draws should be above 5: {}",
+        above
+    );
+
+    assert_eq!(
+        engine.eval::<FLOAT>("empirical_sampler([42]).draw()")?,
+        42.0
+    );
+
+    assert!(engine
+        .eval::<rhai::Dynamic>("empirical_sampler([])")
+        .is_err());
+    assert!(engine
+        .eval::<rhai::Dynamic>("empirical_sampler([1, 'x'])")
+        .is_err());
+
+    Ok(())
+}
+
+#[cfg(all(feature = "float", feature = "array"))]
+#[test]
+fn test_sample_tree() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let mut counts = std::collections::HashMap::new();
+
+    for _ in 0..2000 {
+        let path = engine.eval::<Array>(
+            "
+                sample_tree(#{
+                    common: #{ sword: 6, shield: 3 },
+                    rare: #{ ring: 1, amulet: 0 }
+                })
+            ",
+        )?;
+
+        let path: Vec<_> = path.into_iter().map(|v| v.into_string().unwrap()).collect();
+
+        assert_eq!(path.len(), 2, "Path should reach a leaf: {:?}", path);
+
+        match (path[0].as_str(), path[1].as_str()) {
+            ("common", "sword") | ("common", "shield") | ("rare", "ring") => (),
+            _ => panic!("Invalid path: {:?}", path),
+        }
+
+        *counts.entry(path[0].clone()).or_insert(0) += 1;
+    }
+
+    let common = counts["common"] as FLOAT / 2000.0;
+    assert!(
+        (common - 0.9).abs() < 0.04,
+        "Common branch should be taken 90% of the time: {}",
+        common
+    );
+
+    assert!(engine.eval::<Array>("sample_tree(#{})").is_err());
+    assert!(engine.eval::<Array>("sample_tree(#{ a: #{} })").is_err());
+    assert!(engine.eval::<Array>("sample_tree(#{ a: 'x' })").is_err());
+    assert!(engine
+        .eval::<Array>("sample_tree(#{ a: 0, b: 0 })")
+        .is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_rand_benford_digit() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let mut counts = [0; 10];
+
+    for _ in 0..10000 {
+        let digit = engine.eval::<INT>("rand_benford_digit()")?;
+
+        assert!((1..=9).contains(&digit), "Digit out of range: {}", digit);
+
+        counts[digit as usize] += 1;
+    }
+
+    for (digit, &count) in counts.iter().enumerate().skip(1) {
+        let expected = (1.0 + 1.0 / digit as f64).log10();
+        let ratio = count as f64 / 10000.0;
+
+        assert!(
+            (ratio - expected).abs() < 0.02,
+            "Digit {} should appear about {} of the time: {}",
+            digit,
+            expected,
+            ratio
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(all(feature = "float", feature = "array"))]
+#[test]
+fn test_tiny_weights() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    for _ in 0..100 {
+        // Subnormal weights fall back to uniform selection among the positive weights
+        let roll = engine.eval::<INT>("loaded_die(3, [1e-300 * 1e-20, 0, 1e-300 * 3e-20])")?;
+        assert!(
+            roll == 1 || roll == 3,
+            "Roll should have a positive weight: {}",
+            roll
+        );
+
+        let roll = engine.eval::<INT>("loaded_die(3, [1e-300, 1e-300 * 1e-10, 0])")?;
+        assert!(
+            roll == 1 || roll == 2,
+            "Roll should have a positive weight: {}",
+            roll
+        );
+
+        // Huge weights do not overflow when summed
+        let roll = engine.eval::<INT>("loaded_die(2, [1e308, 1e308])")?;
+        assert!(roll == 1 || roll == 2, "Roll out of range: {}", roll);
+    }
+
+    let counts = engine.eval::<Array>("weighted_counts([1e-300 * 1e-20, 1e-300 * 1e-20], 1000)")?;
+    let counts: Vec<_> = counts.into_iter().map(|v| v.as_int().unwrap()).collect();
+    assert_eq!(counts.iter().sum::<INT>(), 1000);
+    assert!(
+        counts.iter().all(|&n| n > 300),
+        "Should be uniform: {:?}",
+        counts
+    );
+
+    let path = engine.eval::<Array>("sample_tree(#{ a: #{ x: 1e-300 * 1e-20 }, b: 0 })")?;
+    assert_eq!(path[0].clone().into_string().unwrap(), "a");
+
+    assert!(engine.eval::<INT>("loaded_die(2, [0.0, 0.0])").is_err());
+
+    Ok(())
+}
+
+#[cfg(all(feature = "float", feature = "array"))]
+#[test]
+fn test_sample_entries() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let entries = engine.eval::<Array>(
+        r#"
+            let loot = #{
+                gold: #{ chance: 3, amount: 100 },
+                gem: #{ chance: 1.0, amount: 1 },
+                junk: #{ chance: 0, amount: 0 }
+            };
+
+            sample_entries(loot, "chance", 4000)
+        "#,
+    )?;
+
+    assert_eq!(
+        entries.len(),
+        4000,
+        "Should return the requested number of entries"
+    );
+
+    let mut gold = 0;
+
+    for entry in entries {
+        let entry = entry.cast::<rhai::Map>();
+        let key = entry["key"].clone().into_string().unwrap();
+        let value = entry["value"].clone().cast::<rhai::Map>();
+
+        match key.as_str() {
+            "gold" => {
+                assert_eq!(value["amount"].as_int().unwrap(), 100);
+                gold += 1;
+            }
+            "gem" => assert_eq!(value["amount"].as_int().unwrap(), 1),
+            _ => panic!("Zero-weight entries should never be selected: {}", key),
+        }
+    }
+
+    let ratio = gold as FLOAT / 4000.0;
+    assert!(
+        (ratio - 0.75).abs() < 0.04,
+        "Gold should be selected 75% of the time: {}",
+        ratio
+    );
+
+    assert!(engine
+        .eval::<Array>(r#"sample_entries(#{ a: #{ w: 1 } }, "chance", 1)"#)
+        .is_err());
+    assert!(engine
+        .eval::<Array>(r#"sample_entries(#{ a: #{ w: "x" } }, "w", 1)"#)
+        .is_err());
+    assert!(engine
+        .eval::<Array>(r#"sample_entries(#{ a: #{ w: 1 } }, "w", -1)"#)
+        .is_err());
+    assert!(engine
+        .eval::<Array>(r##"sample_entries(#{ a: #{ w: 1 } }, "w", 1 << 50)"##)
+        .is_err());
+
+    Ok(())
+}
+
+#[cfg(all(feature = "float", feature = "array"))]
+#[test]
+fn test_gaussian_field() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let lag1 = |correlation_length: FLOAT| -> Result<FLOAT, Box<EvalAltResult>> {
+        let field =
+            engine.eval::<Array>(&format!("gaussian_field(5000, {:?})", correlation_length))?;
+        let field: Vec<_> = field.into_iter().map(|v| v.as_float().unwrap()).collect();
+
+        assert_eq!(field.len(), 5000);
+
+        let mean = field.iter().sum::<FLOAT>() / 5000.0;
+        let variance = field.iter().map(|x| (x - mean) * (x - mean)).sum::<FLOAT>();
+        let covariance = field
+            .windows(2)
+            .map(|w| (w[0] - mean) * (w[1] - mean))
+            .sum::<FLOAT>();
+
+        Ok(covariance / variance)
+    };
+
+    let short = lag1(1.0)?;
+    let long = lag1(10.0)?;
+
+    assert!(
+        short.abs() < 0.1,
+        "White noise should be uncorrelated: {}",
+        short
+    );
+    assert!(
+        long > 0.8,
+        "Long correlation lengths should be smooth: {}",
+        long
+    );
+
+    assert!(engine.eval::<Array>("gaussian_field(0, 2.0)")?.is_empty());
+    assert!(engine.eval::<Array>("gaussian_field(-1, 2.0)").is_err());
+    assert!(engine.eval::<Array>("gaussian_field(10, 0.0)").is_err());
+    assert!(engine
+        .eval::<Array>("gaussian_field(1 << 40, 2.0)")
+        .is_err());
+    assert_eq!(engine.eval::<Array>("gaussian_field(3, 1e300)")?.len(), 3);
+
+    Ok(())
+}
+
+#[cfg(all(feature = "float", feature = "array"))]
+#[test]
+fn test_last_rejection_ratio() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    for script in [
+        "rand_in_polygon([[0, 0], [10, 0], [0, 1]])",
+        "rand_int_where(|x| x > 90, 1, 100, 1000)",
+        "rand_von_mises(0.0, 1.0)",
+    ] {
+        engine.run(script)?;
+
+        let ratio = engine.eval::<FLOAT>("last_rejection_ratio()")?;
+
+        assert!(
+            ratio > 0.0 && ratio <= 1.0,
+            "Invalid ratio after {}: {}",
+            script,
+            ratio
+        );
+    }
+
+    // Only one in 100 values is accepted, so many attempts are needed on average
+    let mut total = 0.0;
+
+    for _ in 0..100 {
+        engine.run("rand_int_where(|x| x == 1, 1, 100, 10000)")?;
+        total += 1.0 / engine.eval::<FLOAT>("last_rejection_ratio()")?;
+    }
+
+    assert!(
+        total / 100.0 > 50.0,
+        "Average number of attempts should be near 100: {}",
+        total / 100.0
+    );
+
+    engine.run("rand_int_where(|x| false, 1, 100, 10)")?;
+    assert_eq!(engine.eval::<FLOAT>("last_rejection_ratio()")?, 0.0);
+
+    Ok(())
+}
+
+#[test]
+fn test_rand_modular() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    // With a modulus of 2/3 of the full range, `rand() % modulus` would put 2/3 of the
+    // results in the lower half.
+    let modulus = INT::MAX / 3 * 2;
+    let mut low = 0;
+
+    for _ in 0..10000 {
+        let value = engine.eval::<INT>(&format!("rand_modular({})", modulus))?;
+
+        assert!(
+            (0..modulus).contains(&value),
+            "Value out of range: {}",
+            value
+        );
+
+        if value < modulus / 2 {
+            low += 1;
+        }
+    }
+
+    assert!(
+        (low - 5000 as INT).abs() < 300,
+        "Values should be evenly spread: {} in the lower half",
+        low
+    );
+
+    let mut counts = [0; 3];
+
+    for _ in 0..3000 {
+        counts[engine.eval::<INT>("rand_modular(3)")? as usize] += 1;
+    }
+
+    assert!(
+        counts.iter().all(|&n| n > 850),
+        "Values should be evenly spread: {:?}",
+        counts
+    );
+
+    assert!(engine.eval::<INT>("rand_modular(0)").is_err());
+    assert!(engine.eval::<INT>("rand_modular(-5)").is_err());
+
+    Ok(())
+}
+
+#[cfg(all(feature = "float", feature = "array"))]
+#[test]
+fn test_weighted_choice_by_key() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let choose = |key: &str| {
+        engine.eval::<String>(&format!(
+            r#"["a", "b", "c"].weighted_choice_by_key([1, 0, 3], "{}")"#,
+            key
+        ))
+    };
+
+    let mut a = 0;
+
+    for n in 0..4000 {
+        let key = format!("user-{}", n);
+        let choice = choose(&key)?;
+
+        assert_eq!(
+            choice,
+            choose(&key)?,
+            "Same key should give the same choice"
+        );
+        assert_ne!(choice, "b", "Zero-weight elements should never be chosen");
+
+        if choice == "a" {
+            a += 1;
+        }
+    }
+
+    let ratio = a as FLOAT / 4000.0;
+    assert!(
+        (ratio - 0.25).abs() < 0.03,
+        "Should follow the weights: {}",
+        ratio
+    );
+
+    assert!(engine
+        .eval::<()>(r#"[].weighted_choice_by_key([], "x")"#)
+        .is_ok());
+    assert!(engine
+        .eval::<rhai::Dynamic>(r#"[1, 2].weighted_choice_by_key([1], "x")"#)
+        .is_err());
+    assert!(engine
+        .eval::<rhai::Dynamic>(r#"[1, 2].weighted_choice_by_key([1, -1], "x")"#)
+        .is_err());
+
+    Ok(())
+}
+
+#[cfg(all(feature = "float", feature = "array"))]
+#[test]
+fn test_antithetic_array() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let values = engine.eval::<Array>("antithetic_array(500)")?;
+    let values: Vec<_> = values.into_iter().map(|v| v.as_float().unwrap()).collect();
+
+    assert_eq!(values.len(), 1000, "Should return two values per pair");
+
+    for pair in values.chunks(2) {
+        assert!((0.0..=1.0).contains(&pair[0]) && (0.0..=1.0).contains(&pair[1]));
+        assert!(
+            (pair[0] + pair[1] - 1.0).abs() < 1e-12,
+            "Pair should sum to 1: {:?}",
+            pair
+        );
+    }
+
+    assert!(engine.eval::<Array>("antithetic_array(0)")?.is_empty());
+    assert!(engine.eval::<Array>("antithetic_array(-1)").is_err());
+    assert!(engine
+        .eval::<Array>("antithetic_array(9223372036854775807)")
+        .is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_dice_sum() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let mut counts = [0; 19];
+
+    for _ in 0..5000 {
+        let total = engine.eval::<INT>("dice_sum(3, 6)")?;
+
+        assert!((3..=18).contains(&total), "Sum out of range: {}", total);
+
+        counts[total as usize] += 1;
+    }
+
+    // 10 and 11 are the most likely sums of 3d6 (12.5% each), while 3 and 18 are the least
+    // likely (0.5% each); a flat distribution would give each sum 6.25%.
+    assert!(
+        counts[10] > 3 * counts[3] && counts[11] > 3 * counts[18],
+        "Sums should be bell-shaped: {:?}",
+        counts
+    );
+    assert!(
+        counts[10] + counts[11] > 1000,
+        "Middle sums should be most common: {:?}",
+        counts
+    );
+
+    assert_eq!(engine.eval::<INT>("dice_sum(4, 1)")?, 4);
+
+    assert!(engine.eval::<INT>("dice_sum(0, 6)").is_err());
+    assert!(engine.eval::<INT>("dice_sum(-1, 6)").is_err());
+    assert!(engine.eval::<INT>("dice_sum(2, 0)").is_err());
+
+    Ok(())
+}
+
+#[cfg(all(feature = "float", feature = "array"))]
+#[test]
+fn test_rand_hyperexponential() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let mean = engine.eval::<FLOAT>(
+        "
+            let total = 0.0;
+
+            for i in 0..20000 {
+                let x = rand_hyperexponential([4, 0.5], [3, 1]);
+                if x < 0.0 { throw `negative sample: ${x}`; }
+                total += x;
+            }
+
+            total / 20000.0
+        ",
+    )?;
+
+    // Weighted sum of the phase means: 0.75 * 1/4 + 0.25 * 1/0.5 = 0.6875
+    assert!(
+        (mean - 0.6875).abs() < 0.05,
+        "Mean should be near 0.6875: {}",
+        mean
+    );
+
+    assert!(engine
+        .eval::<FLOAT>("rand_hyperexponential([1, 2], [1])")
+        .is_err());
+    assert!(engine
+        .eval::<FLOAT>("rand_hyperexponential([1, 0], [1, 1])")
+        .is_err());
+    assert!(engine
+        .eval::<FLOAT>("rand_hyperexponential([1, 2], [1, -1])")
+        .is_err());
+
+    Ok(())
+}
+
+#[cfg(feature = "array")]
+#[test]
+fn test_rand_permutation_cycles() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    for _ in 0..20 {
+        let cycles = engine.eval::<Array>("rand_permutation_cycles(20)")?;
+        let cycles: Vec<Vec<INT>> = cycles
+            .into_iter()
+            .map(|c| c.into_typed_array::<INT>().unwrap())
+            .collect();
+
+        let mut flat: Vec<_> = cycles.iter().flatten().copied().collect();
+        flat.sort();
+
+        assert_eq!(
+            flat,
+            (0..20).collect::<Vec<INT>>(),
+            "Each index should appear exactly once"
+        );
+
+        // Rebuild the permutation from its cycles and check it is a bijection
+        let mut permutation = [None; 20];
+
+        for cycle in &cycles {
+            assert!(!cycle.is_empty());
+
+            for (i, &from) in cycle.iter().enumerate() {
+                permutation[from as usize] = Some(cycle[(i + 1) % cycle.len()]);
+            }
+        }
+
+        let mut images: Vec<_> = permutation.iter().map(|p| p.unwrap()).collect();
+        images.sort();
+
+        assert_eq!(
+            images,
+            (0..20).collect::<Vec<INT>>(),
+            "Permutation should be valid"
+        );
+    }
+
+    assert!(engine
+        .eval::<Array>("rand_permutation_cycles(0)")?
+        .is_empty());
+    assert!(engine.eval::<Array>("rand_permutation_cycles(-1)").is_err());
+    assert!(engine
+        .eval::<Array>("rand_permutation_cycles(1 << 50)")
+        .is_err());
+
+    Ok(())
+}
+
+#[cfg(all(feature = "float", feature = "array"))]
+#[test]
+fn test_truncated_normal_array() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let values = engine.eval::<Array>("truncated_normal_array(1000, 10.0, 3.0, 3.0, 18.0)")?;
+
+    assert_eq!(values.len(), 1000);
+
+    let values: Vec<FLOAT> = values.into_iter().map(|v| v.as_float().unwrap()).collect();
+
+    assert!(values.iter().all(|&v| (3.0..=18.0).contains(&v)));
+
+    let mean = values.iter().sum::<FLOAT>() / values.len() as FLOAT;
+
+    assert!(
+        (mean - 10.0).abs() < 0.5,
+        "Mean should be near 10.0: {}",
+        mean
+    );
+
+    // A range far into the tail still produces values within bounds
+    let values = engine.eval::<Array>("truncated_normal_array(100, 0.0, 1.0, 8.0, 9.0)")?;
+
+    assert!(values
+        .into_iter()
+        .all(|v| (8.0..=9.0).contains(&v.as_float().unwrap())));
+
+    assert!(engine
+        .eval::<Array>("truncated_normal_array(0, 0.0, 1.0, -1.0, 1.0)")?
+        .is_empty());
+    assert!(engine
+        .eval::<Array>("truncated_normal_array(-1, 0.0, 1.0, -1.0, 1.0)")
+        .is_err());
+    assert!(engine
+        .eval::<Array>("truncated_normal_array(10, 0.0, 0.0, -1.0, 1.0)")
+        .is_err());
+    assert!(engine
+        .eval::<Array>("truncated_normal_array(10, 0.0, 1.0, 1.0, 1.0)")
+        .is_err());
+    assert!(engine
+        .eval::<Array>("truncated_normal_array(1 << 50, 0.0, 1.0, -1.0, 1.0)")
+        .is_err());
+
+    Ok(())
+}
+
+#[cfg(all(feature = "float", feature = "array"))]
+#[test]
+fn test_weighted_indices() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let mut counts = [0; 5];
+
+    for _ in 0..1000 {
+        let picks = engine
+            .eval::<rhai::Dynamic>("weighted_indices([10, 1, 1, 1, 0], 2)")?
+            .into_typed_array::<INT>()?;
+
+        assert_eq!(picks.len(), 2);
+        assert_ne!(picks[0], picks[1], "Indices should be distinct");
+
+        for index in picks {
+            assert!(
+                (0..4).contains(&index),
+                "Zero weights should never be selected"
+            );
+            counts[index as usize] += 1;
+        }
+    }
+
+    assert!(
+        counts[0] > 900,
+        "High-weight index should almost always be selected"
+    );
+    assert!(counts[0] > counts[1] && counts[0] > counts[2] && counts[0] > counts[3]);
+
+    // The number of indices is capped at the number of positive weights
+    let picks = engine.eval::<Array>("weighted_indices([1, 0, 2], 5)")?;
+
+    assert_eq!(picks.len(), 2);
+
+    // Weights too small to be compared reliably fall back to being uniform
+    let mut picked = [false; 2];
+
+    for _ in 0..100 {
+        let picks = engine.eval::<Array>("weighted_indices([1e-320, 1e-320], 1)")?;
+
+        picked[picks[0].as_int().unwrap() as usize] = true;
+    }
+
+    assert_eq!(
+        picked,
+        [true, true],
+        "Tiny weights should not always pick the same index"
+    );
+
+    assert!(engine.eval::<Array>("weighted_indices([], 3)")?.is_empty());
+    assert!(engine
+        .eval::<Array>("weighted_indices([1, -1], 1)")
+        .is_err());
+    assert!(engine
+        .eval::<Array>("weighted_indices([1, 2], -1)")
+        .is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_rand_bool_odds() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let wins = engine.eval::<INT>(
+        "
+            let wins = 0;
+            for i in 0..10000 {
+                if rand_bool_odds(3, 1) { wins += 1; }
+            }
+            wins
+        ",
+    )?;
+
+    assert!(
+        (7250..7750).contains(&wins),
+        "Expected about 75% wins: {}",
+        wins
+    );
+
+    engine.eval::<bool>(&format!("rand_bool_odds({}, {})", INT::MAX, INT::MAX))?;
+
+    assert!(engine.eval::<bool>("rand_bool_odds(0, 1)").is_err());
+    assert!(engine.eval::<bool>("rand_bool_odds(1, -1)").is_err());
+
+    Ok(())
+}
+
+#[cfg(all(feature = "float", feature = "array"))]
+#[test]
+fn test_log_decay_series() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let values: Vec<FLOAT> = engine
+        .eval::<Array>("log_decay_series(64.0, 2, 0.0, 7)")?
+        .into_iter()
+        .map(|v| v.as_float().unwrap())
+        .collect();
+
+    for (i, value) in values.iter().enumerate() {
+        let expected = 64.0 * (0.5 as FLOAT).powf(i as FLOAT / 2.0);
+        assert!((value - expected).abs() < 1e-4, "{} != {}", value, expected);
+    }
+
+    // With noise, the series still decays overall
+    let values: Vec<FLOAT> = engine
+        .eval::<Array>("log_decay_series(1000.0, 10, 0.1, 100)")?
+        .into_iter()
+        .map(|v| v.as_float().unwrap())
+        .collect();
+
+    assert_eq!(values.len(), 100);
+    assert!(values.iter().all(|&v| v > 0.0));
+
+    let head = values[..10].iter().sum::<FLOAT>();
+    let tail = values[90..].iter().sum::<FLOAT>();
+
+    assert!(
+        tail < head / 100.0,
+        "Series should decay: {} vs {}",
+        head,
+        tail
+    );
+
+    assert!(engine
+        .eval::<Array>("log_decay_series(0.0, 1, 0.0, 5)")
+        .is_err());
+    assert!(engine
+        .eval::<Array>("log_decay_series(1.0, 0, 0.0, 5)")
+        .is_err());
+    assert!(engine
+        .eval::<Array>("log_decay_series(1.0, 1, -0.1, 5)")
+        .is_err());
+    assert!(engine
+        .eval::<Array>("log_decay_series(1.0, 1, 0.0, -1)")
+        .is_err());
+    assert!(engine
+        .eval::<Array>("log_decay_series(1.0, 1, 0.0, 1 << 50)")
+        .is_err());
+
+    Ok(())
+}
+
+#[cfg(feature = "array")]
+#[test]
+fn test_rand_int_union() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let mut small = 0;
+    let mut large = 0;
+
+    for _ in 0..10000 {
+        let value = engine.eval::<INT>("rand_int_union([[90, 99], [1, 30], [-5, -5]])")?;
+
+        match value {
+            1..=30 => large += 1,
+            90..=99 | -5 => small += 1,
+            _ => panic!("Value should lie within one of the ranges: {}", value),
+        }
+    }
+
+    // 30 of the 41 integers lie in the larger range
+    let ratio = large as f64 / 10000.0;
+    assert!(
+        (ratio - 30.0 / 41.0).abs() < 0.03,
+        "Unexpected ratio: {}",
+        ratio
+    );
+    assert_eq!(small + large, 10000);
+
+    assert_eq!(engine.eval::<INT>("rand_int_union([[7, 7]])")?, 7);
+    engine.eval::<INT>(&format!("rand_int_union([[{}, {}]])", INT::MIN, INT::MAX))?;
+
+    assert!(engine.eval::<INT>("rand_int_union([])").is_err());
+    assert!(engine.eval::<INT>("rand_int_union([[5, 1]])").is_err());
+    assert!(engine
+        .eval::<INT>("rand_int_union([[1, 5], [5, 9]])")
+        .is_err());
+    assert!(engine.eval::<INT>("rand_int_union([[1, 2, 3]])").is_err());
+    assert!(engine.eval::<INT>("rand_int_union([1, 2])").is_err());
+
+    Ok(())
+}
+
+#[cfg(feature = "array")]
+#[test]
+fn test_sample_windowed() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let mut starts = std::collections::HashSet::new();
+
+    for _ in 0..200 {
+        let mut samples = engine
+            .eval::<rhai::Dynamic>("[0, 1, 2, 3, 4, 5, 6, 7, 8, 9].sample_windowed(4, 3)")?
+            .into_typed_array::<INT>()?;
+
+        samples.sort();
+        samples.dedup();
+
+        assert_eq!(samples.len(), 3, "Samples should be distinct");
+        assert!(
+            samples[2] - samples[0] < 4,
+            "Samples should come from a single window: {:?}",
+            samples
+        );
+
+        starts.insert(samples[0]);
+    }
+
+    assert!(
+        starts.len() > 3,
+        "Windows should start at different positions"
+    );
+
+    // The window is clamped to the array
+    let samples = engine.eval::<Array>("[1, 2, 3].sample_windowed(10, 5)")?;
+    assert_eq!(samples.len(), 3);
+
+    assert!(engine.eval::<Array>("[].sample_windowed(2, 1)")?.is_empty());
+    assert!(engine
+        .eval::<Array>("[1, 2].sample_windowed(0, 1)")
+        .is_err());
+
+    Ok(())
+}
+
+#[cfg(all(feature = "float", feature = "array"))]
+#[test]
+fn test_pink_noise() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    // Fraction of the total power that lies in the averages of blocks of 64 samples,
+    // i.e. in the low frequencies
+    fn low_frequency_power(values: &[FLOAT]) -> FLOAT {
+        let variance = |v: &[FLOAT]| {
+            let mean = v.iter().sum::<FLOAT>() / v.len() as FLOAT;
+            v.iter().map(|x| (x - mean).powi(2)).sum::<FLOAT>() / v.len() as FLOAT
+        };
+        let blocks: Vec<FLOAT> = values
+            .chunks(64)
+            .map(|c| c.iter().sum::<FLOAT>() / c.len() as FLOAT)
+            .collect();
+
+        variance(&blocks) / variance(values)
+    }
+
+    let pink: Vec<FLOAT> = engine
+        .eval::<Array>("pink_noise(8192)")?
+        .into_iter()
+        .map(|v| v.as_float().unwrap())
+        .collect();
+
+    assert_eq!(pink.len(), 8192);
+    assert!(pink.iter().all(|v| (-1.0..=1.0).contains(v)));
+
+    let white: Vec<FLOAT> = engine
+        .eval::<Array>("let x = []; for i in 0..8192 { x.push(rand_float(-1.0, 1.0)); } x")?
+        .into_iter()
+        .map(|v| v.as_float().unwrap())
+        .collect();
+
+    let pink_power = low_frequency_power(&pink);
+    let white_power = low_frequency_power(&white);
+
+    assert!(
+        pink_power > 5.0 * white_power,
+        "Pink noise should have more low-frequency power: {} vs {}",
+        pink_power,
+        white_power
+    );
+
+    assert!(engine.eval::<Array>("pink_noise(0)")?.is_empty());
+    assert!(engine.eval::<Array>("pink_noise(-1)").is_err());
+    assert!(engine.eval::<Array>("pink_noise(1 << 50)").is_err());
+
+    Ok(())
+}
+
+#[cfg(feature = "array")]
+#[test]
+fn test_rand_mask() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let mut masks = std::collections::HashSet::new();
+
+    for _ in 0..20 {
+        let mask = engine
+            .eval::<rhai::Dynamic>("rand_mask(20, 7)")?
+            .into_typed_array::<bool>()?;
+
+        assert_eq!(mask.len(), 20);
+        assert_eq!(mask.iter().filter(|&&b| b).count(), 7);
+
+        masks.insert(mask);
+    }
+
+    assert!(masks.len() > 1, "Positions should vary across runs");
+
+    let mask = engine
+        .eval::<rhai::Dynamic>("rand_mask(5, 5)")?
+        .into_typed_array::<bool>()?;
+    assert_eq!(mask, vec![true; 5]);
+
+    assert!(engine.eval::<Array>("rand_mask(0, 0)")?.is_empty());
+    assert!(engine.eval::<Array>("rand_mask(5, 6)").is_err());
+    assert!(engine.eval::<Array>("rand_mask(5, -1)").is_err());
+    assert!(engine.eval::<Array>("rand_mask(-1, 0)").is_err());
+    assert!(engine
+        .eval::<rhai::Dynamic>("rand_mask(1 << 50, 1)")
+        .is_err());
+
+    Ok(())
+}
+
+#[cfg(all(feature = "float", feature = "array"))]
+#[test]
+fn test_gaussian_copula_2d() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    // Spearman's rank correlation of a list of pairs
+    fn rank_correlation(pairs: &[(FLOAT, FLOAT)]) -> FLOAT {
+        let ranks = |values: Vec<FLOAT>| {
+            let mut order: Vec<usize> = (0..values.len()).collect();
+            order.sort_by(|&a, &b| values[a].total_cmp(&values[b]));
+            let mut ranks = vec![0.0; values.len()];
+            for (rank, index) in order.into_iter().enumerate() {
+                ranks[index] = rank as FLOAT;
+            }
+            ranks
+        };
+        let u = ranks(pairs.iter().map(|p| p.0).collect());
+        let v = ranks(pairs.iter().map(|p| p.1).collect());
+        let n = pairs.len() as FLOAT;
+        let d2: FLOAT = u.iter().zip(&v).map(|(a, b)| (a - b).powi(2)).sum();
+
+        1.0 - 6.0 * d2 / (n * (n * n - 1.0))
+    }
+
+    for rho in [0.8, 0.0, -0.5] {
+        let pairs: Vec<(FLOAT, FLOAT)> = (0..2000)
+            .map(|_| {
+                let pair = engine
+                    .eval::<rhai::Dynamic>(&format!("gaussian_copula_2d({:?})", rho))
+                    .unwrap()
+                    .into_typed_array::<FLOAT>()
+                    .unwrap();
+                assert_eq!(pair.len(), 2);
+                (pair[0], pair[1])
+            })
+            .collect();
+
+        assert!(pairs
+            .iter()
+            .all(|&(u, v)| (0.0..1.0).contains(&u) && (0.0..1.0).contains(&v)));
+
+        // Rank correlation of a Gaussian copula is `6/π * asin(rho/2)`
+        let expected = 6.0 / std::f64::consts::PI as FLOAT * (rho / 2.0 as FLOAT).asin();
+        let actual = rank_correlation(&pairs);
+
+        assert!(
+            (actual - expected).abs() < 0.07,
+            "Rank correlation for rho = {}: expected {}, got {}",
+            rho,
+            expected,
+            actual
+        );
+    }
+
+    assert!(engine.eval::<Array>("gaussian_copula_2d(1.0)").is_err());
+    assert!(engine.eval::<Array>("gaussian_copula_2d(-1.0)").is_err());
+
+    Ok(())
+}
+
+#[cfg(feature = "array")]
+#[test]
+fn test_sample_balanced() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    for _ in 0..50 {
+        let samples = engine
+            .eval::<rhai::Dynamic>(
+                r#"
+                    let x = [10, 11, 12, 13, 20, 21, 30];
+                    let labels = ["a", "a", "a", "a", "b", "b", 3];
+                    x.sample_balanced(labels, 3)
+                "#,
+            )?
+            .into_typed_array::<INT>()?;
+
+        // 3 from "a", both from "b", and the only element labeled 3
+        assert_eq!(samples.len(), 6);
+        assert!(samples[..3].iter().all(|v| (10..=13).contains(v)));
+
+        let mut rest = samples[3..].to_vec();
+        rest.sort();
+        assert_eq!(rest, vec![20, 21, 30]);
+
+        let mut first = samples[..3].to_vec();
+        first.sort();
+        first.dedup();
+        assert_eq!(first.len(), 3, "Samples should not repeat");
+    }
+
+    assert!(engine
+        .eval::<Array>("[1, 2].sample_balanced([0, 1], 0)")?
+        .is_empty());
+    assert!(engine
+        .eval::<Array>("[1, 2].sample_balanced([0], 1)")
+        .is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_rand_bits_set() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let mut values = std::collections::HashSet::new();
+
+    for _ in 0..100 {
+        let value = engine.eval::<INT>("rand_bits_set(12, 5)")?;
+
+        assert_eq!(value.count_ones(), 5);
+        assert!((0..1 << 12).contains(&value));
+
+        values.insert(value);
+    }
+
+    assert!(values.len() > 10, "Bit positions should vary");
+
+    assert_eq!(engine.eval::<INT>("rand_bits_set(0, 0)")?, 0);
+    assert_eq!(engine.eval::<INT>("rand_bits_set(63, 63)")?, INT::MAX);
+
+    assert!(engine.eval::<INT>("rand_bits_set(64, 1)").is_err());
+    assert!(engine.eval::<INT>("rand_bits_set(4, 5)").is_err());
+    assert!(engine.eval::<INT>("rand_bits_set(4, -1)").is_err());
+
+    Ok(())
+}
+
+#[cfg(all(feature = "float", feature = "array"))]
+#[test]
+fn test_sample_spline() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let mut xs = Vec::new();
+
+    for _ in 0..100 {
+        // Control points on the parabola `y = x^2`, which Catmull-Rom follows exactly
+        let point = engine
+            .eval::<rhai::Dynamic>(
+                "sample_spline([[0.0, 0.0], [1.0, 1.0], [2.0, 4.0], [3.0, 9.0], [4.0, 16.0]])",
+            )?
+            .into_typed_array::<FLOAT>()?;
+
+        assert_eq!(point.len(), 2);
+
+        let (x, y) = (point[0], point[1]);
+
+        assert!((0.0..=4.0).contains(&x) && (0.0..=16.0).contains(&y));
+        assert!(
+            (1.0..=3.0).contains(&x),
+            "Spline spans the inner points: {}",
+            x
+        );
+        assert!((y - x * x).abs() < 1e-4, "Point should lie on the spline");
+
+        xs.push(x);
+    }
+
+    xs.sort_by(|a, b| a.total_cmp(b));
+    assert!(xs[99] - xs[0] > 1.0, "Points should vary along the spline");
+
+    assert!(engine
+        .eval::<Array>("sample_spline([[0, 0], [1, 1], [2, 2]])")
+        .is_err());
+    assert!(engine
+        .eval::<Array>("sample_spline([[0, 0], [1, 1], [2, 2], [3]])")
+        .is_err());
+
+    Ok(())
+}
+
+#[cfg(all(feature = "float", feature = "array"))]
+#[test]
+fn test_weighted_sampler() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let counts = engine
+        .eval::<rhai::Dynamic>(
+            "
+                let sampler = weighted_sampler([1, 1, 1, 1, 1]);
+                let counts = [0, 0, 0, 0, 0];
+
+                for i in 0..5000 { counts[sampler.draw()] += 1; }
+
+                sampler.update_weight(0, 0.0);
+                sampler.update_weight(3, 6.0);
+
+                for i in 0..10000 { counts[sampler.draw()] += 1; }
+
+                counts
+            ",
+        )?
+        .into_typed_array::<INT>()?;
+
+    // Before the update: about 1000 each; after: 0, 1111, 1111, 6667, 1111
+    assert!((800..1200).contains(&counts[0]), "{:?}", counts);
+    assert!((1800..2400).contains(&counts[1]), "{:?}", counts);
+    assert!((1800..2400).contains(&counts[2]), "{:?}", counts);
+    assert!((7300..8000).contains(&counts[3]), "{:?}", counts);
+    assert!((1800..2400).contains(&counts[4]), "{:?}", counts);
+
+    // Only the remaining positive weight is drawn
+    assert_eq!(
+        engine.eval::<INT>(
+            "
+                let sampler = weighted_sampler([3, 0, 2]);
+                sampler.update_weight(0, 0);
+                sampler.draw()
+            "
+        )?,
+        2
+    );
+
+    assert!(engine
+        .eval::<INT>("weighted_sampler([0, 0]).draw()")
+        .is_err());
+    assert!(engine
+        .eval::<rhai::Dynamic>("weighted_sampler([])")
+        .is_err());
+    assert!(engine
+        .eval::<rhai::Dynamic>("weighted_sampler([1, -1])")
+        .is_err());
+    assert!(engine
+        .eval::<()>("let s = weighted_sampler([1, 2]); s.update_weight(2, 1.0);")
+        .is_err());
+    assert!(engine
+        .eval::<()>("let s = weighted_sampler([1, 2]); s.update_weight(0, -1.0);")
+        .is_err());
+
+    Ok(())
+}
+
+#[cfg(feature = "float")]
+#[test]
+fn test_rand_rayleigh() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let mean = engine.eval::<FLOAT>(
+        "
+            let total = 0.0;
+            for i in 0..10000 {
+                let x = rand_rayleigh(2.0);
+                if x < 0.0 { throw `Negative result: ${x}`; }
+                total += x;
+            }
+            total / 10000.0
+        ",
+    )?;
+
+    let expected = 2.0 * (std::f64::consts::PI as FLOAT / 2.0).sqrt();
+
+    assert!(
+        (mean - expected).abs() < 0.05,
+        "Mean should be near {}: {}",
+        expected,
+        mean
+    );
+
+    assert!(engine.eval::<FLOAT>("rand_rayleigh(0.0)").is_err());
+    assert!(engine.eval::<FLOAT>("rand_rayleigh(-1.0)").is_err());
+
+    Ok(())
+}
+
+#[cfg(feature = "float")]
+#[test]
+fn test_rand_negative_binomial() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let mean = engine.eval::<FLOAT>(
+        "
+            let total = 0;
+            for i in 0..10000 {
+                let x = rand_negative_binomial(3, 0.4);
+                if x < 0 { throw `Negative result: ${x}`; }
+                total += x;
+            }
+            total.to_float() / 10000.0
+        ",
+    )?;
+
+    // 3 * (1 - 0.4) / 0.4 = 4.5
+    assert!(
+        (mean - 4.5).abs() < 0.2,
+        "Mean should be near 4.5: {}",
+        mean
+    );
+
+    assert_eq!(engine.eval::<INT>("rand_negative_binomial(5, 1.0)")?, 0);
+
+    assert!(engine
+        .eval::<INT>("rand_negative_binomial(0, 0.5)")
+        .is_err());
+    assert!(engine
+        .eval::<INT>("rand_negative_binomial(1, 0.0)")
+        .is_err());
+    assert!(engine
+        .eval::<INT>("rand_negative_binomial(1, 1.5)")
+        .is_err());
+
+    Ok(())
+}
+
+#[cfg(feature = "float")]
+#[test]
+fn test_rand_kumaraswamy() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    // The mode of a Kumaraswamy distribution is `((a - 1) / (a * b - 1)) ^ (1 / a)`
+    for (a, b) in [(2.0 as FLOAT, 5.0), (5.0, 2.0)] {
+        let mut bins = [0; 10];
+
+        for _ in 0..20000 {
+            let x = engine.eval::<FLOAT>(&format!("rand_kumaraswamy({:?}, {:?})", a, b))?;
+
+            assert!(x > 0.0 && x < 1.0, "Result should be in (0, 1): {}", x);
+
+            bins[(x * 10.0) as usize] += 1;
+        }
+
+        let mode = ((a - 1.0) / (a * b - 1.0)).powf(1.0 / a);
+        let peak = (0..10).max_by_key(|&i| bins[i]).unwrap();
+
+        assert!(
+            (peak as isize - (mode * 10.0) as isize).abs() <= 1,
+            "Peak bin {} should be near the mode {}: {:?}",
+            peak,
+            mode,
+            bins
+        );
+    }
+
+    let x = engine.eval::<FLOAT>("rand_kumaraswamy(0.001, 1000.0)")?;
+    assert!(x > 0.0 && x < 1.0);
+
+    assert!(engine.eval::<FLOAT>("rand_kumaraswamy(0.0, 1.0)").is_err());
+    assert!(engine.eval::<FLOAT>("rand_kumaraswamy(1.0, -1.0)").is_err());
+
+    Ok(())
+}
+
+#[cfg(feature = "array")]
+#[test]
+fn test_sample_where() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let mut counts = [0; 10];
+
+    for _ in 0..1000 {
+        let value =
+            engine.eval::<INT>("[0, 1, 2, 3, 4, 5, 6, 7, 8, 9].sample_where(|v| v % 2 == 0)")?;
+
+        assert_eq!(
+            value % 2,
+            0,
+            "Only even values should be returned: {}",
+            value
+        );
+
+        counts[value as usize] += 1;
+    }
+
+    assert!(
+        counts.iter().step_by(2).all(|&c| (130..270).contains(&c)),
+        "Matches should be equally likely: {:?}",
+        counts
+    );
+
+    assert!(engine
+        .eval::<rhai::Dynamic>("[1, 3, 5].sample_where(|v| v % 2 == 0)")?
+        .is_unit());
+    assert!(engine
+        .eval::<rhai::Dynamic>("[].sample_where(|v| true)")?
+        .is_unit());
+    assert!(engine
+        .eval::<rhai::Dynamic>("[1, 2].sample_where(|v| 42)")
+        .is_err());
+
+    Ok(())
+}
+
+#[cfg(all(feature = "float", feature = "array"))]
+#[test]
+fn test_sample_near_quantile() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    // Samples 0 to 100 in reverse order, so the quantile of each sample is `value / 100`
+    let script = "
+        let samples = [];
+        for i in 0..=100 { samples.push(100 - i); }
+        let values = [];
+        for i in 0..500 { values.push(sample_near_quantile(samples, 0.9, 0.05)); }
+        values
+    ";
+
+    let values = engine.eval::<Array>(script)?;
+    let values: Vec<FLOAT> = values.into_iter().map(|v| v.as_float().unwrap()).collect();
+
+    assert!(
+        values.iter().all(|v| (85.0..=95.0).contains(v)),
+        "{:?}",
+        values
+    );
+
+    let mean = values.iter().sum::<FLOAT>() / values.len() as FLOAT;
+    assert!(
+        (mean - 90.0).abs() < 1.0,
+        "Values should cluster near 90: {}",
+        mean
+    );
+
+    assert_eq!(
+        engine.eval::<FLOAT>("sample_near_quantile([3, 1, 2], 1.0, 0.0)")?,
+        3.0
+    );
+    assert_eq!(
+        engine.eval::<FLOAT>("sample_near_quantile([5], 0.3, 0.0)")?,
+        5.0
+    );
+
+    assert!(engine
+        .eval::<FLOAT>("sample_near_quantile([], 0.5, 0.1)")
+        .is_err());
+    assert!(engine
+        .eval::<FLOAT>("sample_near_quantile([1, 2], 1.5, 0.1)")
+        .is_err());
+    assert!(engine
+        .eval::<FLOAT>("sample_near_quantile([1, 2], 0.5, -0.1)")
+        .is_err());
+    assert!(engine
+        .eval::<FLOAT>(r#"sample_near_quantile([1, "x"], 0.5, 0.1)"#)
+        .is_err());
+
+    Ok(())
+}
+
+#[cfg(feature = "array")]
+#[test]
+fn test_rand_sequence_distinct_adjacent() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    for _ in 0..20 {
+        let values = engine
+            .eval::<rhai::Dynamic>("rand_sequence_distinct_adjacent(100, 1, 3)")?
+            .into_typed_array::<INT>()?;
+
+        assert_eq!(values.len(), 100);
+        assert!(values.iter().all(|v| (1..=3).contains(v)));
+        assert!(
+            values.windows(2).all(|w| w[0] != w[1]),
+            "Adjacent values should differ: {:?}",
+            values
+        );
+    }
+
+    let values = engine
+        .eval::<rhai::Dynamic>("rand_sequence_distinct_adjacent(10, 0, 1)")?
+        .into_typed_array::<INT>()?;
+    assert!(values.windows(2).all(|w| w[0] != w[1]));
+
+    assert_eq!(
+        engine
+            .eval::<rhai::Dynamic>("rand_sequence_distinct_adjacent(1, 5, 5)")?
+            .into_typed_array::<INT>()?,
+        vec![5]
+    );
+    assert!(engine
+        .eval::<Array>("rand_sequence_distinct_adjacent(0, 1, 3)")?
+        .is_empty());
+
+    assert!(engine
+        .eval::<Array>("rand_sequence_distinct_adjacent(2, 5, 5)")
+        .is_err());
+    assert!(engine
+        .eval::<Array>("rand_sequence_distinct_adjacent(2, 5, 1)")
+        .is_err());
+    assert!(engine
+        .eval::<Array>("rand_sequence_distinct_adjacent(-1, 1, 3)")
+        .is_err());
+    assert!(engine
+        .eval::<rhai::Dynamic>("rand_sequence_distinct_adjacent(1 << 50, 1, 3)")
+        .is_err());
+
+    Ok(())
+}
+
+#[cfg(all(feature = "float", feature = "array"))]
+#[test]
+fn test_weighted_blend() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let values = engine.eval::<Array>(
+        "
+            let values = [];
+            for i in 0..1000 { values.push(weighted_blend([5.0, -2, 3.5, 1.0], [1, 2, 0, 10])); }
+            values
+        ",
+    )?;
+    let values: Vec<FLOAT> = values.into_iter().map(|v| v.as_float().unwrap()).collect();
+
+    assert!(
+        values.iter().all(|v| (-2.0..=5.0).contains(v)),
+        "{:?}",
+        values
+    );
+
+    // Most blends involve the heavily-weighted `1.0`
+    let near = values
+        .iter()
+        .filter(|v| (-2.0..=5.0).contains(*v) && (**v - 1.0).abs() < 2.0);
+    assert!(near.count() > 700);
+
+    assert_eq!(engine.eval::<FLOAT>("weighted_blend([4.0], [1])")?, 4.0);
+    assert_eq!(
+        engine.eval::<FLOAT>("weighted_blend([4.0, 9.0], [1, 0])")?,
+        4.0
+    );
+
+    assert!(engine.eval::<FLOAT>("weighted_blend([], [])").is_err());
+    assert!(engine
+        .eval::<FLOAT>("weighted_blend([1.0, 2.0], [1])")
+        .is_err());
+    assert!(engine
+        .eval::<FLOAT>("weighted_blend([1.0, 2.0], [1, -1])")
+        .is_err());
+    assert!(engine
+        .eval::<FLOAT>("weighted_blend([1.0, 2.0], [0, 0])")
+        .is_err());
+
+    Ok(())
+}
+
+#[cfg(all(feature = "float", feature = "array"))]
+#[test]
+fn test_riffle_shuffle() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    // Number of cards still directly followed by their original successor
+    fn ordered_pairs(deck: &[INT]) -> usize {
+        deck.windows(2).filter(|w| w[1] == w[0] + 1).count()
+    }
+
+    let mut once = 0;
+    let mut seven = 0;
+
+    for _ in 0..20 {
+        for (riffles, total) in [(1, &mut once), (7, &mut seven)] {
+            let mut deck = engine
+                .eval::<rhai::Dynamic>(&format!(
+                    "
+                        let deck = [];
+                        for card in 0..52 {{ deck.push(card); }}
+                        for i in 0..{} {{ deck.riffle_shuffle(1.0); }}
+                        deck
+                    ",
+                    riffles
+                ))?
+                .into_typed_array::<INT>()?;
+
+            *total += ordered_pairs(&deck);
+
+            deck.sort();
+            assert_eq!(
+                deck,
+                (0..52).collect::<Vec<INT>>(),
+                "All cards should remain"
+            );
+        }
+    }
+
+    assert!(
+        once > 5 * seven,
+        "One riffle should mix less than seven: {} vs {}",
+        once,
+        seven
+    );
+
+    // A perfect cut always splits the deck in the middle
+    let deck = engine
+        .eval::<rhai::Dynamic>("let deck = [0, 1, 2, 3]; deck.riffle_shuffle(0.0); deck")?
+        .into_typed_array::<INT>()?;
+    let position = |card: INT| deck.iter().position(|&c| c == card).unwrap();
+    assert!(position(0) < position(1) && position(2) < position(3));
+
+    engine.run("let deck = []; deck.riffle_shuffle(0.5);")?;
+    assert!(engine
+        .run("let deck = [1, 2]; deck.riffle_shuffle(1.5);")
+        .is_err());
+
+    Ok(())
+}
+
+#[cfg(all(feature = "float", feature = "array"))]
+#[test]
+fn test_clustered_int_array() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let values = engine
+        .eval::<rhai::Dynamic>("clustered_int_array(5000, 40, 10.0, 0, 100)")?
+        .into_typed_array::<INT>()?;
+
+    assert_eq!(values.len(), 5000);
+    assert!(values.iter().all(|v| (0..=100).contains(v)));
+
+    let mean = values.iter().sum::<INT>() as f64 / values.len() as f64;
+    assert!(
+        (mean - 40.0).abs() < 1.0,
+        "Mean should be near 40: {}",
+        mean
+    );
+
+    // Values are clamped to the range
+    let values = engine
+        .eval::<rhai::Dynamic>("clustered_int_array(100, 0, 50.0, -3, 3)")?
+        .into_typed_array::<INT>()?;
+    assert!(values.iter().all(|v| (-3..=3).contains(v)));
+
+    let values = engine
+        .eval::<rhai::Dynamic>("clustered_int_array(10, 7, 0.0, 0, 10)")?
+        .into_typed_array::<INT>()?;
+    assert_eq!(values, vec![7; 10]);
+
+    assert!(engine
+        .eval::<Array>("clustered_int_array(0, 0, 1.0, 0, 10)")?
+        .is_empty());
+    assert!(engine
+        .eval::<Array>("clustered_int_array(-1, 0, 1.0, 0, 10)")
+        .is_err());
+    assert!(engine
+        .eval::<Array>("clustered_int_array(5, 0, -1.0, 0, 10)")
+        .is_err());
+    assert!(engine
+        .eval::<Array>("clustered_int_array(5, 0, 1.0, 10, 0)")
+        .is_err());
+    assert!(engine
+        .eval::<rhai::Dynamic>("clustered_int_array(1 << 50, 0, 1.0, -5, 5)")
+        .is_err());
+
+    Ok(())
+}
+
+#[cfg(all(feature = "float", feature = "array"))]
+#[test]
+fn test_kde_sample() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let samples: [FLOAT; 5] = [1.5, 2.0, 4.0, 7.0, 10.5];
+
+    for _ in 0..100 {
+        let value = engine.eval::<FLOAT>("kde_sample([1.5, 2, 4.0, 7, 10.5], 0.0)")?;
+        assert!(
+            samples.contains(&value),
+            "Should return a sample: {}",
+            value
+        );
+    }
+
+    let mean = engine.eval::<FLOAT>(
+        "
+            let total = 0.0;
+            for i in 0..10000 { total += kde_sample([1.5, 2, 4.0, 7, 10.5], 1.0); }
+            total / 10000.0
+        ",
+    )?;
+
+    // The sample mean is 5.0
+    assert!(
+        (mean - 5.0).abs() < 0.15,
+        "Mean should be near 5.0: {}",
+        mean
+    );
+
+    assert!(engine.eval::<FLOAT>("kde_sample([], 1.0)").is_err());
+    assert!(engine.eval::<FLOAT>("kde_sample([1.0], -1.0)").is_err());
+    assert!(engine.eval::<FLOAT>(r#"kde_sample(["x"], 1.0)"#).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_rand_seed() -> Result<(), Box<EvalAltResult>> {
+    let script = "
+        let values = [];
+        for i in 0..10 { values.push(rand(1, 1000000)); }
+        values
+    ";
+
+    let mut engine = Engine::new();
+    engine.register_global_module(RandomPackage::with_seed(12345).as_shared_module());
+
+    let first = engine
+        .eval::<rhai::Dynamic>(script)?
+        .into_typed_array::<INT>()?;
+
+    // Re-seeding the same package restarts the sequence
+    engine.run("rand_seed(12345)")?;
+    let second = engine
+        .eval::<rhai::Dynamic>(script)?
+        .into_typed_array::<INT>()?;
+
+    assert_eq!(first, second);
+
+    // A new package with the same seed generates the same sequence
+    let mut engine = Engine::new();
+    engine.register_global_module(RandomPackage::with_seed(12345).as_shared_module());
+
+    let third = engine
+        .eval::<rhai::Dynamic>(script)?
+        .into_typed_array::<INT>()?;
+
+    assert_eq!(first, third);
+
+    // Repeated calls advance the same stream, and other seeds give other sequences
+    let next = engine
+        .eval::<rhai::Dynamic>(script)?
+        .into_typed_array::<INT>()?;
+    assert_ne!(first, next);
+
+    engine.run("rand_seed(54321)")?;
+    let other = engine
+        .eval::<rhai::Dynamic>(script)?
+        .into_typed_array::<INT>()?;
+    assert_ne!(first, other);
+
+    Ok(())
+}
+
+#[test]
+fn test_rand_seed_independent_packages() -> Result<(), Box<EvalAltResult>> {
+    let script = "
+        let values = [];
+        for i in 0..10 { values.push(rand(1, 1000000)); }
+        values
+    ";
+
+    let mut seeded = Engine::new();
+    seeded.register_global_module(RandomPackage::with_seed(1).as_shared_module());
+
+    // A package created later on the same thread is not seeded
+    let mut unseeded = Engine::new();
+    unseeded.register_global_module(RandomPackage::new().as_shared_module());
+
+    let first = unseeded
+        .eval::<rhai::Dynamic>(script)?
+        .into_typed_array::<INT>()?;
+    let second = unseeded
+        .eval::<rhai::Dynamic>(script)?
+        .into_typed_array::<INT>()?;
+    assert_ne!(first, second);
+
+    // Using the unseeded package does not advance the seeded one
+    let expected = {
+        let mut engine = Engine::new();
+        engine.register_global_module(RandomPackage::with_seed(1).as_shared_module());
+        engine
+            .eval::<rhai::Dynamic>(script)?
+            .into_typed_array::<INT>()?
+    };
+    assert_eq!(
+        seeded
+            .eval::<rhai::Dynamic>(script)?
+            .into_typed_array::<INT>()?,
+        expected
+    );
+
+    // Seeding from a script only affects the package it is called from
+    unseeded.run("rand_seed(1)")?;
+    seeded.run("rand_clear_seed()")?;
+
+    assert_eq!(
+        unseeded
+            .eval::<rhai::Dynamic>(script)?
+            .into_typed_array::<INT>()?,
+        expected
+    );
+    assert_ne!(
+        seeded
+            .eval::<rhai::Dynamic>(script)?
+            .into_typed_array::<INT>()?,
+        expected
+    );
+
+    Ok(())
+}
+
+#[cfg(feature = "array")]
+#[test]
+fn test_rand_seed_array() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let script = "
+        rand_seed(-7);
+        let x = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
+        x.shuffle();
+        [x, x.sample(3), x.sample()]
+    ";
+
+    let first = engine.eval::<Array>(script)?;
+    let second = engine.eval::<Array>(script)?;
+
+    assert_eq!(format!("{:?}", first), format!("{:?}", second));
+
+    Ok(())
+}
+
+#[cfg(all(feature = "float", feature = "array"))]
+#[test]
+fn test_sample_power_biased() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    // Fraction of picks that select the first element
+    let front_share = |alpha: FLOAT| -> Result<f64, Box<EvalAltResult>> {
+        let picks = engine
+            .eval::<rhai::Dynamic>(&format!(
+                "[0, 1, 2, 3, 4, 5, 6, 7, 8, 9].sample_power_biased(5000, {:?})",
+                alpha
+            ))?
+            .into_typed_array::<INT>()?;
+
+        assert_eq!(picks.len(), 5000);
+        assert!(picks.iter().all(|v| (0..10).contains(v)));
+
+        Ok(picks.iter().filter(|&&v| v == 0).count() as f64 / 5000.0)
+    };
+
+    let uniform = front_share(0.0)?;
+    let biased = front_share(1.0)?;
+    let heavy = front_share(3.0)?;
+
+    assert!(
+        (uniform - 0.1).abs() < 0.03,
+        "alpha = 0 should be uniform: {}",
+        uniform
+    );
+    assert!(
+        uniform < biased && biased < heavy,
+        "{} < {} < {}",
+        uniform,
+        biased,
+        heavy
+    );
+    assert!(
+        heavy > 0.75,
+        "alpha = 3 should mostly pick the first element: {}",
+        heavy
+    );
+
+    assert!(engine
+        .eval::<Array>("[].sample_power_biased(3, 1.0)")?
+        .is_empty());
+    assert!(engine
+        .eval::<Array>("[1, 2].sample_power_biased(-1, 1.0)")
+        .is_err());
+    assert!(engine
+        .eval::<Array>("[1, 2].sample_power_biased(1, -1.0)")
+        .is_err());
+    assert!(engine
+        .eval::<Array>("[1, 2, 3].sample_power_biased(1 << 40, 1.0)")
+        .is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_rand_not_optimized_away() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.set_optimization_level(OptimizationLevel::Full);
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let values = engine
+        .eval::<rhai::Dynamic>(
+            "
+                let values = [];
+                for i in 0..10 { values.push(rand(1, 10)); }
+                values
+            ",
+        )?
+        .into_typed_array::<INT>()?;
+
+    assert!(
+        values.iter().any(|&v| v != values[0]),
+        "Values should not be constant-folded: {:?}",
+        values
+    );
+
+    Ok(())
+}
+
+#[cfg(feature = "float")]
+#[test]
+fn test_rand_normal() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let mean = engine.eval::<FLOAT>(
+        "
+            let total = 0.0;
+            for i in 0..5000 { total += rand_normal(10.0, 2.0); }
+            total / 5000.0
+        ",
+    )?;
+
+    assert!(
+        (mean - 10.0).abs() < 0.15,
+        "Mean should be near 10.0: {}",
+        mean
+    );
+
+    assert_eq!(engine.eval::<FLOAT>("rand_normal(3.5, 0.0)")?, 3.5);
+    assert!(engine.eval::<FLOAT>("rand_normal(0.0, -1.0)").is_err());
+
+    Ok(())
+}
+
+#[cfg(all(feature = "float", feature = "array"))]
+#[test]
+fn test_truncated_normal_vector() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let bounds: [(FLOAT, FLOAT); 3] = [(0.0, 1.0), (-5.0, -4.0), (100.0, 200.0)];
+
+    for _ in 0..500 {
+        let vector = engine
+            .eval::<rhai::Dynamic>(
+                "truncated_normal_vector([0.5, 0, 150], [0.2, 1.0, 1000.0], [0, -5, 100], [1, -4, 200])",
+            )?
+            .into_typed_array::<FLOAT>()?;
+
+        assert_eq!(vector.len(), 3);
+
+        for (value, (lower, upper)) in vector.iter().zip(bounds) {
+            assert!(
+                (lower..=upper).contains(value),
+                "{} should be within {}..{}",
+                value,
+                lower,
+                upper
+            );
+        }
+    }
+
+    assert!(engine
+        .eval::<Array>("truncated_normal_vector([], [], [], [])")?
+        .is_empty());
+    assert!(engine
+        .eval::<Array>("truncated_normal_vector([0, 0], [1], [0, 0], [1, 1])")
+        .is_err());
+    assert!(engine
+        .eval::<Array>("truncated_normal_vector([0], [0], [0], [1])")
+        .is_err());
+    assert!(engine
+        .eval::<Array>("truncated_normal_vector([0], [1], [1], [1])")
+        .is_err());
+    assert!(engine
+        .eval::<Array>(r#"truncated_normal_vector(["x"], [1], [0], [1])"#)
+        .is_err());
+
+    Ok(())
+}
+
+#[cfg(feature = "float")]
+#[test]
+fn test_rand_exp() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let mean = engine.eval::<FLOAT>(
+        "
+            let total = 0.0;
+            for i in 0..10000 {
+                let x = rand_exp(4.0);
+                if x < 0.0 { throw `Negative result: ${x}`; }
+                total += x;
+            }
+            total / 10000.0
+        ",
+    )?;
+
+    assert!(
+        (mean - 0.25).abs() < 0.015,
+        "Mean should be near 0.25: {}",
+        mean
+    );
+
+    assert!(engine.eval::<FLOAT>("rand_exp(0.0)").is_err());
+    assert!(engine.eval::<FLOAT>("rand_exp(-1.0)").is_err());
+
+    Ok(())
+}
+
+#[cfg(all(feature = "float", feature = "array"))]
+#[test]
+fn test_weighted_rotate() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let mut counts = [0; 4];
+
+    for _ in 0..4000 {
+        let rotated = engine
+            .eval::<rhai::Dynamic>("let x = [0, 1, 2, 3]; x.weighted_rotate([5, 3, 0, 2]); x")?
+            .into_typed_array::<INT>()?;
+
+        let offset = rotated[0];
+
+        assert_eq!(
+            rotated,
+            (0..4).map(|i| (offset + i) % 4).collect::<Vec<INT>>(),
+            "Should be a rotation of the original"
+        );
+
+        counts[offset as usize] += 1;
+    }
+
+    assert!((1800..2200).contains(&counts[0]), "{:?}", counts);
+    assert!((1000..1400).contains(&counts[1]), "{:?}", counts);
+    assert_eq!(counts[2], 0, "{:?}", counts);
+    assert!((600..1000).contains(&counts[3]), "{:?}", counts);
+
+    engine.run("let x = []; x.weighted_rotate([]);")?;
+
+    assert!(engine
+        .run("let x = [1, 2]; x.weighted_rotate([1]);")
+        .is_err());
+    assert!(engine
+        .run("let x = [1, 2]; x.weighted_rotate([1, -1]);")
+        .is_err());
+
+    Ok(())
+}
+
+#[cfg(feature = "float")]
+#[test]
+fn test_rand_binomial() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let mean = engine.eval::<FLOAT>(
+        "
+            let total = 0;
+            for i in 0..5000 {
+                let x = rand_binomial(20, 0.3);
+                if x < 0 || x > 20 { throw `Result out of range: ${x}`; }
+                total += x;
+            }
+            total.to_float() / 5000.0
+        ",
+    )?;
+
+    assert!(
+        (mean - 6.0).abs() < 0.15,
+        "Mean should be near 6.0: {}",
+        mean
+    );
+
+    assert_eq!(engine.eval::<INT>("rand_binomial(0, 0.5)")?, 0);
+    assert_eq!(engine.eval::<INT>("rand_binomial(10, 0.0)")?, 0);
+    assert_eq!(engine.eval::<INT>("rand_binomial(10, 1.0)")?, 10);
+
+    assert!(engine.eval::<INT>("rand_binomial(-1, 0.5)").is_err());
+    assert!(engine.eval::<INT>("rand_binomial(10, 1.5)").is_err());
+    assert!(engine.eval::<INT>("rand_binomial(10, -0.1)").is_err());
+
+    Ok(())
+}
+
+#[cfg(all(feature = "float", feature = "array"))]
+#[test]
+fn test_sample_recency() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let mut counts = [0; 4];
+
+    for _ in 0..3000 {
+        let index = engine.eval::<INT>("[0, 1, 2, 3].sample_recency(2.0)")?;
+        counts[index as usize] += 1;
+    }
+
+    // Weights 1, 2, 4, 8 out of 15
+    assert!(
+        counts.windows(2).all(|w| w[0] < w[1]),
+        "Later elements should be chosen more often: {:?}",
+        counts
+    );
+    assert!((1450..1750).contains(&counts[3]), "{:?}", counts);
+
+    // Long arrays do not overflow the weights
+    engine.eval::<INT>("let x = []; for i in 0..2000 { x.push(i); } x.sample_recency(10.0)")?;
+
+    assert!(engine.eval::<INT>("[].sample_recency(2.0)").is_err());
+    assert!(engine.eval::<INT>("[1, 2].sample_recency(1.0)").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_rand_char() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    for _ in 0..200 {
+        let ch = engine.eval::<char>("rand_char()")?;
+        assert!(('!'..='~').contains(&ch), "Not printable ASCII: {:?}", ch);
+
+        let ch = engine.eval::<char>("rand_char('a', 'f')")?;
+        assert!(('a'..='f').contains(&ch), "Out of range: {:?}", ch);
+
+        // A range spanning the surrogate code points still returns valid characters
+        let ch = engine.eval::<char>(&format!("rand_char('{}', '{}')", '\u{D7FE}', '\u{E001}'))?;
+        assert!(
+            ('\u{D7FE}'..='\u{D7FF}').contains(&ch) || ('\u{E000}'..='\u{E001}').contains(&ch),
+            "Out of range: {:?}",
+            ch
+        );
+    }
+
+    assert_eq!(engine.eval::<char>("rand_char('x', 'x')")?, 'x');
+    assert!(engine.eval::<char>("rand_char('z', 'a')").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_rand_string() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    for len in [1, 8, 100] {
+        let s = engine.eval::<String>(&format!("rand_string({})", len))?;
+
+        assert_eq!(s.len(), len);
+        assert!(
+            s.chars().all(|ch| ch.is_ascii_alphanumeric()),
+            "Not alphanumeric: {:?}",
+            s
+        );
+    }
+
+    assert_eq!(engine.eval::<String>("rand_string(0)")?, "");
+    assert_eq!(engine.eval::<String>("rand_string(-5)")?, "");
+    assert!(engine.eval::<String>("rand_string(1 << 40)").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_rand_bytes() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let first = engine.eval::<rhai::Blob>("rand_bytes(32)")?;
+    let second = engine.eval::<rhai::Blob>("rand_bytes(32)")?;
+
+    assert_eq!(first.len(), 32);
+    assert_eq!(second.len(), 32);
+    assert!(first != second);
+
+    assert!(engine.eval::<rhai::Blob>("rand_bytes(0)")?.is_empty());
+    assert!(engine.eval::<rhai::Blob>("rand_bytes(-5)")?.is_empty());
+    assert!(engine.eval::<rhai::Blob>("rand_bytes(1 << 40)").is_err());
+
+    Ok(())
+}
+
+#[cfg(feature = "float")]
+#[test]
+fn test_rand_triangular_unit() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let mut buckets = [0; 5];
+
+    for _ in 0..5000 {
+        let value = engine.eval::<FLOAT>("rand_triangular_unit()")?;
+
+        assert!((0.0..=1.0).contains(&value), "Out of range: {}", value);
+
+        buckets[((value * 5.0) as usize).min(4)] += 1;
+    }
+
+    // The middle bucket around the mode should be the most frequent
+    assert!(
+        buckets.iter().all(|&count| count <= buckets[2]),
+        "Values should peak near 0.5: {:?}",
+        buckets
+    );
+
+    Ok(())
+}
+
+#[cfg(all(feature = "float", feature = "array"))]
+#[test]
+fn test_bursty_flips() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    for (p, avg_run) in [(0.5, 4.0), (0.3, 5.0), (0.5, 2.0)] {
+        let values = engine
+            .eval::<rhai::Dynamic>(&format!("bursty_flips(40000, {:?}, {:?})", p, avg_run))?
+            .into_typed_array::<bool>()?;
+
+        assert_eq!(values.len(), 40000);
+
+        let ratio = values.iter().filter(|&&v| v).count() as f64 / values.len() as f64;
+        let runs = 1 + values.windows(2).filter(|w| w[0] != w[1]).count();
+        let observed = values.len() as f64 / runs as f64;
+
+        assert!(
+            (ratio - p).abs() < 0.03,
+            "True ratio should be near {}: {}",
+            p,
+            ratio
+        );
+        assert!(
+            (observed - avg_run).abs() < avg_run * 0.1,
+            "Average run length should be near {}: {}",
+            avg_run,
+            observed
+        );
+    }
+
+    let values = engine
+        .eval::<rhai::Dynamic>("bursty_flips(100, 1.0, 3.0)")?
+        .into_typed_array::<bool>()?;
+    assert!(values.iter().all(|&v| v));
+
+    // An unreachable run length still keeps the true ratio
+    let values = engine
+        .eval::<rhai::Dynamic>("bursty_flips(20000, 0.1, 1.0)")?
+        .into_typed_array::<bool>()?;
+    let ratio = values.iter().filter(|&&v| v).count() as f64 / values.len() as f64;
+    assert!(
+        (ratio - 0.1).abs() < 0.02,
+        "True ratio should be near 0.1: {}",
+        ratio
+    );
+
+    assert!(engine
+        .eval::<Array>("bursty_flips(0, 0.5, 2.0)")?
+        .is_empty());
+    assert!(engine.eval::<Array>("bursty_flips(-1, 0.5, 2.0)").is_err());
+    assert!(engine.eval::<Array>("bursty_flips(10, 1.5, 2.0)").is_err());
+    assert!(engine.eval::<Array>("bursty_flips(10, 0.5, 0.5)").is_err());
+    assert!(engine
+        .eval::<rhai::Dynamic>("bursty_flips(1 << 50, 0.5, 2.0)")
+        .is_err());
+
+    Ok(())
+}
+
+#[cfg(feature = "array")]
+#[test]
+fn test_make_drawer() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let drawn = engine.eval::<Array>(
+        "
+            let drawer = make_drawer([1, 2, 3, 4, 5, 6, 7, 8, 9, 10]);
+            let drawn = [];
+
+            loop {
+                let x = drawer.draw_next();
+                if x == () { break; }
+                drawn.push(x);
+            }
+
+            // Exhausted drawers keep returning ()
+            if drawer.draw_next() != () { throw \"Drawer should be exhausted\"; }
+
+            drawn
+        ",
+    )?;
+
+    let mut drawn = drawn
+        .into_iter()
+        .map(|v| v.as_int().unwrap())
+        .collect::<Vec<_>>();
+    drawn.sort_unstable();
+
+    assert_eq!(drawn, (1..=10).collect::<Vec<INT>>());
+
+    assert!(engine.eval::<()>("make_drawer([]).draw_next()").is_ok());
+
+    Ok(())
+}
+
+#[cfg(feature = "array")]
+#[test]
+fn test_rand_monotonic() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let values = engine
+        .eval::<rhai::Dynamic>("rand_monotonic(1000, -50, 50)")?
+        .into_typed_array::<INT>()?;
+
+    assert_eq!(values.len(), 1000);
+    assert!(values.iter().all(|v| (-50..=50).contains(v)));
+    assert!(values.windows(2).all(|w| w[0] <= w[1]));
+
+    let values = engine
+        .eval::<rhai::Dynamic>("rand_monotonic(5, 3, 3)")?
+        .into_typed_array::<INT>()?;
+    assert_eq!(values, vec![3; 5]);
+
+    assert!(engine.eval::<Array>("rand_monotonic(0, 0, 10)")?.is_empty());
+    assert!(engine.eval::<Array>("rand_monotonic(-1, 0, 10)").is_err());
+    assert!(engine.eval::<Array>("rand_monotonic(5, 10, 0)").is_err());
+    assert!(engine
+        .eval::<rhai::Dynamic>("rand_monotonic(1 << 50, 0, 10)")
+        .is_err());
+
+    Ok(())
+}
+
+#[cfg(feature = "float")]
+#[test]
+fn test_rand_float_seeded() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let first = engine.eval::<FLOAT>("rand_float_seeded(42, 10.0, 20.0)")?;
+    let second = engine.eval::<FLOAT>("rand_float_seeded(42, 10.0, 20.0)")?;
+    let other = engine.eval::<FLOAT>("rand_float_seeded(43, 10.0, 20.0)")?;
+
+    assert!((10.0..20.0).contains(&first), "Out of range: {}", first);
+    assert_eq!(first, second, "Same seed should give the same value");
+    assert!(
+        first != other,
+        "Different seeds should give different values"
+    );
+
+    // The main stream of random values is not disturbed
+    let values = engine.eval::<rhai::Dynamic>(
+        "
+            rand_seed(1);
+            let a = rand();
+            rand_seed(1);
+            rand_float_seeded(7, 0.0, 1.0);
+            [a, rand()]
+        ",
+    )?;
+    let values = values.into_typed_array::<INT>()?;
+    assert_eq!(values[0], values[1]);
+
+    assert!(engine
+        .eval::<FLOAT>("rand_float_seeded(1, 5.0, 5.0)")
+        .is_err());
+    assert!(engine
+        .eval::<FLOAT>("rand_float_seeded(1, 5.0, 1.0)")
+        .is_err());
+
+    Ok(())
+}
+
+#[cfg(feature = "array")]
+#[test]
+fn test_shuffle_rounds() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    // Total distance of all elements from their original positions
+    fn displacement(array: &[INT]) -> usize {
+        array
+            .iter()
+            .enumerate()
+            .map(|(i, &v)| (v - i as INT).unsigned_abs() as usize)
+            .sum()
+    }
+
+    let mut light = 0;
+    let mut heavy = 0;
+
+    for _ in 0..20 {
+        for (rounds, total) in [(2, &mut light), (200, &mut heavy)] {
+            let mut array = engine
+                .eval::<rhai::Dynamic>(&format!(
+                    "
+                        let a = [];
+                        for i in 0..50 {{ a.push(i); }}
+                        a.shuffle_rounds({});
+                        a
+                    ",
+                    rounds
+                ))?
+                .into_typed_array::<INT>()?;
+
+            *total += displacement(&array);
+
+            array.sort();
+            assert_eq!(
+                array,
+                (0..50).collect::<Vec<INT>>(),
+                "Array should remain a permutation"
+            );
+        }
+    }
+
+    assert!(
+        heavy > 5 * light,
+        "More rounds should displace more: {} vs {}",
+        heavy,
+        light
+    );
+
+    let array = engine
+        .eval::<rhai::Dynamic>("let a = [1, 2, 3]; a.shuffle_rounds(0); a")?
+        .into_typed_array::<INT>()?;
+    assert_eq!(array, vec![1, 2, 3]);
+
+    assert!(engine.eval::<()>("[].shuffle_rounds(5)").is_ok());
+    assert!(engine.eval::<()>("[1, 2].shuffle_rounds(-1)").is_err());
+
+    Ok(())
+}
+
+#[cfg(all(feature = "float", feature = "array"))]
+#[test]
+fn test_sample_weighted() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let counts = engine
+        .eval::<rhai::Dynamic>(
+            "
+                let x = ['a', 'b', 'c'];
+                let counts = [0, 0, 0];
+                for i in 0..1000 {
+                    counts[x.index_of(x.sample_weighted([1, 50, 1.5]))] += 1;
+                }
+                counts
+            ",
+        )?
+        .into_typed_array::<INT>()?;
+
+    assert!(
+        counts[1] > 10 * (counts[0] + counts[2]),
+        "Heavily-weighted element should be selected far more often: {:?}",
+        counts
+    );
+
+    assert_eq!(
+        engine.eval::<char>("['a', 'b'].sample_weighted([0, 2])")?,
+        'b'
+    );
+    assert_eq!(engine.eval::<()>("[].sample_weighted([])")?, ());
+
+    assert!(engine
+        .eval::<char>("['a', 'b'].sample_weighted([1])")
+        .is_err());
+    assert!(engine
+        .eval::<char>("['a', 'b'].sample_weighted([1, -1])")
+        .is_err());
+    assert!(engine
+        .eval::<char>("['a', 'b'].sample_weighted([0, 0])")
+        .is_err());
+
+    Ok(())
+}
+
+#[cfg(feature = "array")]
+#[test]
+fn test_sample_with_replacement() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let values = engine
+        .eval::<rhai::Dynamic>("[1, 2, 3].sample_with_replacement(100)")?
+        .into_typed_array::<INT>()?;
+
+    assert_eq!(values.len(), 100);
+    assert!(values.iter().all(|v| (1..=3).contains(v)));
+    assert!(
+        (1..=3).all(|n| values.contains(&n)),
+        "Every element should be drawn at least once: {:?}",
+        values
+    );
+
+    assert!(engine
+        .eval::<Array>("[].sample_with_replacement(5)")?
+        .is_empty());
+    assert!(engine
+        .eval::<Array>("[1, 2, 3].sample_with_replacement(0)")?
+        .is_empty());
+    assert!(engine
+        .eval::<Array>("[1, 2, 3].sample_with_replacement(-1)")?
+        .is_empty());
+    assert!(engine
+        .eval::<Array>("[1, 2].sample_with_replacement(1 << 62)")
+        .is_err());
+
+    Ok(())
+}
+
+#[cfg(all(feature = "float", feature = "array"))]
+#[test]
+fn test_weighted_sample_stable() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let picks = engine
+        .eval::<rhai::Dynamic>(
+            "
+                let picks = [];
+                for seed in 0..12 {
+                    picks.push(weighted_sample_stable([0, 1, 2, 3], [1, 2, 3, 4], seed));
+                }
+                picks
+            ",
+        )?
+        .into_typed_array::<INT>()?;
+
+    // Golden values: these must never change between versions
+    assert_eq!(picks, vec![3, 2, 3, 3, 3, 1, 2, 1, 0, 1, 2, 2]);
+
+    // The result does not depend on the package's random number generator
+    assert_eq!(
+        engine
+            .eval::<INT>("rand_seed(123); weighted_sample_stable([0, 1, 2, 3], [1, 2, 3, 4], 0)")?,
+        3
+    );
+
+    assert_eq!(
+        engine.eval::<char>("weighted_sample_stable(['a', 'b', 'c'], [0, 5, 0], 99)")?,
+        'b'
+    );
+    assert_eq!(engine.eval::<()>("weighted_sample_stable([], [], 1)")?, ());
+
+    assert!(engine
+        .eval::<INT>("weighted_sample_stable([1, 2], [1], 1)")
+        .is_err());
+    assert!(engine
+        .eval::<INT>("weighted_sample_stable([1, 2], [1, -1], 1)")
+        .is_err());
+    assert!(engine
+        .eval::<INT>("weighted_sample_stable([1, 2], [0, 0], 1)")
+        .is_err());
+
+    // Huge weights do not overflow the total, so both elements can still be selected
+    let mut picked = [false; 2];
+
+    for seed in 0..100 {
+        let item = engine.eval::<INT>(&format!(
+            "weighted_sample_stable([0, 1], [1e308, 1e308], {})",
+            seed
+        ))?;
+
+        picked[item as usize] = true;
+    }
+
+    assert_eq!(picked, [true, true]);
+
+    Ok(())
+}
+
+#[cfg(all(feature = "float", feature = "array"))]
+#[test]
+fn test_rand_array_mean() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    for target in [10.0, 50.0, 75.0, 97.5] {
+        let values = engine
+            .eval::<rhai::Dynamic>(&format!("rand_array_mean(5000, 0, 100, {:?})", target))?
+            .into_typed_array::<INT>()?;
+
+        assert_eq!(values.len(), 5000);
+        assert!(values.iter().all(|v| (0..=100).contains(v)));
+
+        let mean = values.iter().sum::<INT>() as f64 / values.len() as f64;
+        assert!(
+            (mean - target).abs() < 1.5,
+            "Mean should be near {}: {}",
+            target,
+            mean
+        );
+    }
+
+    let values = engine
+        .eval::<rhai::Dynamic>("rand_array_mean(10, 3, 8, 8.0)")?
+        .into_typed_array::<INT>()?;
+    assert_eq!(values, vec![8; 10]);
+
+    let values = engine
+        .eval::<rhai::Dynamic>("rand_array_mean(10, 5, 5, 5.0)")?
+        .into_typed_array::<INT>()?;
+    assert_eq!(values, vec![5; 10]);
+
+    assert!(engine
+        .eval::<Array>("rand_array_mean(0, 0, 10, 5.0)")?
+        .is_empty());
+    assert!(engine
+        .eval::<Array>("rand_array_mean(-1, 0, 10, 5.0)")
+        .is_err());
+    assert!(engine
+        .eval::<Array>("rand_array_mean(5, 10, 0, 5.0)")
+        .is_err());
+    assert!(engine
+        .eval::<Array>("rand_array_mean(5, 0, 10, 11.0)")
+        .is_err());
+    assert!(engine
+        .eval::<rhai::Dynamic>("rand_array_mean(1 << 50, 0, 10, 5.0)")
+        .is_err());
+
+    Ok(())
+}
+
+#[cfg(feature = "array")]
+#[test]
+fn test_shuffle_with_seed() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let shuffle = |seed: INT| -> Result<Vec<INT>, Box<EvalAltResult>> {
+        Ok(engine
+            .eval::<rhai::Dynamic>(&format!(
+                "
+                    let a = [];
+                    for i in 0..20 {{ a.push(i); }}
+                    a.shuffle({});
+                    a
+                ",
+                seed
+            ))?
+            .into_typed_array::<INT>()?)
+    };
+
+    let first = shuffle(42)?;
+
+    assert_eq!(first, shuffle(42)?, "Same seed should give the same order");
+    assert!(
+        first != shuffle(43)?,
+        "Different seeds should give different orders"
+    );
+    assert!(first != (0..20).collect::<Vec<INT>>());
+
+    let mut sorted = first.clone();
+    sorted.sort();
+    assert_eq!(sorted, (0..20).collect::<Vec<INT>>());
+
+    Ok(())
+}
+
+#[cfg(feature = "array")]
+#[test]
+fn test_shuffle_partial() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let mut counts = [0; 10];
+
+    for _ in 0..500 {
+        let mut array = engine
+            .eval::<rhai::Dynamic>(
+                "
+                    let a = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9];
+                    a.shuffle_partial(3);
+                    a
+                ",
+            )?
+            .into_typed_array::<INT>()?;
+
+        for &v in &array[..3] {
+            counts[v as usize] += 1;
+        }
+
+        array.sort();
+        assert_eq!(
+            array,
+            (0..10).collect::<Vec<INT>>(),
+            "Array should remain a permutation"
+        );
+    }
+
+    // Every element should appear among the first three about 150 times
+    assert!(
+        counts.iter().all(|&count| (90..=210).contains(&count)),
+        "First elements should be a uniformly random subset: {:?}",
+        counts
+    );
+
+    let array = engine
+        .eval::<rhai::Dynamic>("let a = [1, 2, 3]; a.shuffle_partial(-1); a")?
+        .into_typed_array::<INT>()?;
+    assert_eq!(array, vec![1, 2, 3]);
+
+    let mut array = engine
+        .eval::<rhai::Dynamic>("let a = [1, 2, 3]; a.shuffle_partial(10); a")?
+        .into_typed_array::<INT>()?;
+    array.sort();
+    assert_eq!(array, vec![1, 2, 3]);
+
+    assert!(engine.eval::<()>("[].shuffle_partial(3)").is_ok());
+
+    Ok(())
+}
+
+#[cfg(all(feature = "float", feature = "array"))]
+#[test]
+fn test_gp_sample() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let mut near = 0.0;
+    let mut far = 0.0;
+    let mut squares = 0.0;
+
+    for _ in 0..200 {
+        let values = engine
+            .eval::<rhai::Dynamic>("gp_sample([0.0, 0.1, 5.0, 5.1, 10.0], 1.0, 4.0)")?
+            .into_typed_array::<FLOAT>()?;
+
+        assert_eq!(values.len(), 5);
+
+        near += (values[0] - values[1]).abs() + (values[2] - values[3]).abs();
+        far += (values[0] - values[2]).abs() + (values[2] - values[4]).abs();
+        squares += values.iter().map(|v| v * v).sum::<FLOAT>();
+    }
+
+    assert!(
+        far > 5.0 * near,
+        "Nearby points should have similar values: {} vs {}",
+        near,
+        far
+    );
+
+    let variance = squares / 1000.0;
+    assert!(
+        (variance - 4.0).abs() < 0.8,
+        "Variance should be near 4.0: {}",
+        variance
+    );
+
+    // Repeated points share the same value
+    let values = engine
+        .eval::<rhai::Dynamic>("gp_sample([1, 1, 2], 1.0, 1.0)")?
+        .into_typed_array::<FLOAT>()?;
+    assert!((values[0] - values[1]).abs() < 1e-6);
+
+    assert!(engine.eval::<Array>("gp_sample([], 1.0, 1.0)")?.is_empty());
+    assert!(engine.eval::<Array>("gp_sample([2, 1], 1.0, 1.0)").is_err());
+    assert!(engine
+        .eval::<Array>("gp_sample([1, 'a'], 1.0, 1.0)")
+        .is_err());
+    assert!(engine.eval::<Array>("gp_sample([1, 2], 0.0, 1.0)").is_err());
+    assert!(engine
+        .eval::<Array>("gp_sample([1, 2], 1.0, -1.0)")
+        .is_err());
+    let err = engine
+        .eval::<Array>("let x = []; x.pad(100000, 0.0); gp_sample(x, 1.0, 1.0)")
+        .unwrap_err();
+    assert!(err.to_string().contains("Too many points"), "{}", err);
+
+    Ok(())
+}
+
+#[cfg(feature = "array")]
+#[test]
+fn test_sample_index() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let mut seen = [false; 4];
+
+    for _ in 0..200 {
+        let index = engine.eval::<INT>("['a', 'a', 'b', 'c'].sample_index()")?;
+
+        assert!((0..4).contains(&index), "Out of range: {}", index);
+        seen[index as usize] = true;
+    }
+
+    assert!(
+        seen.iter().all(|&s| s),
+        "Every index should be returned: {:?}",
+        seen
+    );
+
+    assert_eq!(engine.eval::<INT>("[].sample_index()")?, -1);
+
+    Ok(())
+}
+
+#[cfg(all(feature = "float", feature = "array"))]
+#[test]
+fn test_roll_loot() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let counts = engine
+        .eval::<rhai::Dynamic>(
+            "
+                let tiers = [
+                    #{ chance: 0.7, items: [1, 2, 3] },
+                    #{ chance: 0.25, items: [10, 20] },
+                    #{ chance: 0.05, items: [100] },
+                    #{ chance: 0, items: [] }
+                ];
+                let counts = [0, 0, 0];
+                for i in 0..4000 {
+                    let item = roll_loot(tiers);
+                    if item < 10 { counts[0] += 1; }
+                    else if item < 100 { counts[1] += 1; }
+                    else { counts[2] += 1; }
+                    if !(item in [1, 2, 3, 10, 20, 100]) { throw `Unknown item: ${item}`; }
+                }
+                counts
+            ",
+        )?
+        .into_typed_array::<INT>()?;
+
+    for (count, chance) in counts.iter().zip([0.7, 0.25, 0.05]) {
+        let frequency = *count as f64 / 4000.0;
+        assert!(
+            (frequency - chance).abs() < 0.03,
+            "Tier frequency should be near {}: {}",
+            chance,
+            frequency
+        );
+    }
+
+    assert!(engine.eval::<INT>("roll_loot([])").is_err());
+    assert!(engine
+        .eval::<INT>("roll_loot([#{ chance: -1, items: [1] }])")
+        .is_err());
+    assert!(engine
+        .eval::<INT>("roll_loot([#{ chance: 1, items: [] }])")
+        .is_err());
+    assert!(engine.eval::<INT>("roll_loot([#{ items: [1] }])").is_err());
+    assert!(engine.eval::<INT>("roll_loot([#{ chance: 1 }])").is_err());
+    assert!(engine.eval::<INT>("roll_loot([1])").is_err());
+    assert!(engine
+        .eval::<INT>("roll_loot([#{ chance: 0, items: [1] }])")
+        .is_err());
+
+    Ok(())
+}
+
+#[cfg(feature = "array")]
+#[test]
+fn test_sample_flat() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let counts = engine
+        .eval::<rhai::Dynamic>(
+            "
+                let arrays = [[0], [], [1, 1, 1, 1], [2, 2, 2, 2, 2]];
+                let counts = [0, 0, 0];
+                for i in 0..5000 { counts[arrays.sample_flat()] += 1; }
+                counts
+            ",
+        )?
+        .into_typed_array::<INT>()?;
+
+    for (count, size) in counts.iter().zip([1.0, 4.0, 5.0]) {
+        let frequency = *count as f64 / 5000.0;
+        assert!(
+            (frequency - size / 10.0).abs() < 0.03,
+            "Frequency should be proportional to the size {}: {}",
+            size,
+            frequency
+        );
+    }
+
+    assert_eq!(engine.eval::<()>("[].sample_flat()")?, ());
+    assert_eq!(engine.eval::<()>("[[], []].sample_flat()")?, ());
+    assert!(engine.eval::<INT>("[[1], 2].sample_flat()").is_err());
+
+    Ok(())
+}
+
+#[cfg(feature = "array")]
+#[test]
+fn test_rand_array() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let values = engine
+        .eval::<rhai::Dynamic>("rand_array(100)")?
+        .into_typed_array::<INT>()?;
+
+    assert_eq!(values.len(), 100);
+    assert!(values.windows(2).any(|w| w[0] != w[1]));
+
+    let values = engine
+        .eval::<rhai::Dynamic>("rand_array(1000, -5..5)")?
+        .into_typed_array::<INT>()?;
+
+    assert_eq!(values.len(), 1000);
+    assert!(values.iter().all(|v| (-5..5).contains(v)));
+    assert!(values.contains(&-5) && values.contains(&4));
+
+    assert!(engine.eval::<Array>("rand_array(0)")?.is_empty());
+    assert!(engine.eval::<Array>("rand_array(-3)")?.is_empty());
+    assert!(engine.eval::<Array>("rand_array(-3, 0..10)")?.is_empty());
+    assert!(engine.eval::<Array>("rand_array(1 << 40)").is_err());
+    assert!(engine.eval::<Array>("rand_array(5, 3..3)").is_err());
+
+    Ok(())
+}
+
+#[cfg(feature = "array")]
+#[test]
+fn test_rand_jittered_grid() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    for _ in 0..50 {
+        let values = engine
+            .eval::<rhai::Dynamic>("rand_jittered_grid(10, 100, 199)")?
+            .into_typed_array::<INT>()?;
+
+        assert_eq!(values.len(), 10);
+        assert!(values.windows(2).all(|w| w[0] < w[1]));
+
+        for (i, v) in values.iter().enumerate() {
+            let cell = 100 + 10 * i as INT;
+            assert!(
+                (cell..cell + 10).contains(v),
+                "Value {} should be in cell {}..{}",
+                v,
+                cell,
+                cell + 10
+            );
+        }
+    }
+
+    let values = engine
+        .eval::<rhai::Dynamic>("rand_jittered_grid(5, 1, 5)")?
+        .into_typed_array::<INT>()?;
+    assert_eq!(values, vec![1, 2, 3, 4, 5]);
+
+    let values = engine
+        .eval::<rhai::Dynamic>(&format!(
+            "rand_jittered_grid(2, {}, {})",
+            INT::MIN,
+            INT::MAX
+        ))?
+        .into_typed_array::<INT>()?;
+    assert!(values[0] < 0 && values[1] >= 0);
+
+    assert!(engine
+        .eval::<Array>("rand_jittered_grid(0, 0, 10)")
+        .is_err());
+    assert!(engine.eval::<Array>("rand_jittered_grid(5, 0, 3)").is_err());
+    assert!(engine.eval::<Array>("rand_jittered_grid(1, 5, 0)").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_rand_ratio() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let hits = engine.eval::<INT>(
+        "
+            let hits = 0;
+            for i in 0..10000 { if rand_ratio(1, 4) { hits += 1; } }
+            hits
+        ",
+    )?;
+
+    let rate = hits as f64 / 10000.0;
+    assert!(
+        (rate - 0.25).abs() < 0.02,
+        "True rate should be near 0.25: {}",
+        rate
+    );
+
+    assert!(!engine.eval::<bool>("rand_ratio(0, 5)")?);
+    assert!(engine.eval::<bool>("rand_ratio(5, 5)")?);
+
+    assert!(engine.eval::<bool>("rand_ratio(1, 0)").is_err());
+    assert!(engine.eval::<bool>("rand_ratio(6, 5)").is_err());
+    assert!(engine.eval::<bool>("rand_ratio(-1, 5)").is_err());
+    assert!(engine.eval::<bool>("rand_ratio(-1, -5)").is_err());
+
+    Ok(())
+}
+
+#[cfg(all(feature = "float", feature = "array"))]
+#[test]
+fn test_adaptive_roll() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let rate = |history: &str| -> Result<f64, Box<EvalAltResult>> {
+        let hits = engine.eval::<INT>(&format!(
+            "
+                let hits = 0;
+                for i in 0..4000 {{ if adaptive_roll({}, 0.5, 0.3) {{ hits += 1; }} }}
+                hits
+            ",
+            history
+        ))?;
+
+        Ok(hits as f64 / 4000.0)
+    };
+
+    let after_failures = rate("[false, false, false, true]")?;
+    let after_successes = rate("[true, true, true, false]")?;
+    let neutral = rate("[]")?;
+
+    // Expected rates: 0.65, 0.35 and 0.5
+    assert!(
+        (after_failures - 0.65).abs() < 0.04,
+        "Rate should rise after failures: {}",
+        after_failures
+    );
+    assert!(
+        (after_successes - 0.35).abs() < 0.04,
+        "Rate should fall after successes: {}",
+        after_successes
+    );
+    assert!(
+        (neutral - 0.5).abs() < 0.04,
+        "Rate should be the base probability: {}",
+        neutral
+    );
+
+    // The probability is clamped
+    assert!(engine.eval::<bool>("adaptive_roll([false], 0.9, 5.0)")?);
+    assert!(!engine.eval::<bool>("adaptive_roll([true], 0.1, 5.0)")?);
+
+    assert!(engine.eval::<bool>("adaptive_roll([], 1.5, 0.1)").is_err());
+    assert!(engine.eval::<bool>("adaptive_roll([], 0.5, -0.1)").is_err());
+    assert!(engine.eval::<bool>("adaptive_roll([1], 0.5, 0.1)").is_err());
+
+    Ok(())
+}
+
+#[cfg(feature = "float")]
+#[test]
+fn test_rand_arc_angle() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let pi = std::f64::consts::PI;
+
+    for center in [0.5, 3.0, -3.0] {
+        let mut lower_half = false;
+        let mut upper_half = false;
+
+        for _ in 0..500 {
+            let angle = engine.eval::<FLOAT>(&format!("rand_arc_angle({:?}, 0.6)", center))?;
+
+            assert!(
+                angle > -pi && angle <= pi,
+                "Angle should be in (-PI, PI]: {}",
+                angle
+            );
+
+            // Signed angular distance from the center, accounting for wraparound
+            let offset = (angle - center + pi).rem_euclid(2.0 * pi) - pi;
+
+            assert!(
+                offset.abs() <= 0.3 + 1e-9,
+                "Angle {} should be within the arc around {}",
+                angle,
+                center
+            );
+
+            lower_half |= offset < 0.0;
+            upper_half |= offset > 0.0;
+        }
+
+        assert!(lower_half && upper_half, "Angles should cover the arc");
+    }
+
+    assert_eq!(engine.eval::<FLOAT>("rand_arc_angle(1.0, 0.0)")?, 1.0);
+    assert!(engine.eval::<FLOAT>("rand_arc_angle(1.0, -0.1)").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_rand_sign() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let mut counts = [0, 0];
+
+    for _ in 0..200 {
+        match engine.eval::<INT>("rand_sign()")? {
+            -1 => counts[0] += 1,
+            1 => counts[1] += 1,
+            sign => panic!("Invalid sign: {}", sign),
+        }
+    }
+
+    assert!(
+        counts[0] > 0 && counts[1] > 0,
+        "Both signs should appear: {:?}",
+        counts
+    );
+
+    Ok(())
+}
+
+#[cfg(feature = "array")]
+#[test]
+fn test_make_shuffler() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let script = |seed: INT| {
+        format!(
+            "
+                let shuffler = make_shuffler({});
+                let a = [];
+                for i in 0..20 {{ a.push(i); }}
+                let rounds = [];
+                for round in 0..3 {{ rounds.push(shuffler.shuffle_with(a)); }}
+                rand();     // other functions do not affect the shuffler
+                rounds.push(shuffler.shuffle_with(a));
+                rounds
+            ",
+            seed
+        )
+    };
+
+    let first = engine.eval::<Array>(&script(7))?;
+    let second = engine.eval::<Array>(&script(7))?;
+    let other = engine.eval::<Array>(&script(8))?;
+
+    let rounds = |rounds: Array| -> Result<Vec<Vec<INT>>, Box<EvalAltResult>> {
+        rounds
+            .into_iter()
+            .map(|round| Ok(round.into_typed_array::<INT>()?))
+            .collect()
+    };
+    let (first, second, other) = (rounds(first)?, rounds(second)?, rounds(other)?);
+
+    assert_eq!(first, second, "Same seed should give the same orders");
+    assert!(
+        first != other,
+        "Different seeds should give different orders"
+    );
+    assert!(
+        first[0] != first[1],
+        "Each shuffle should advance the generator"
+    );
+
+    for round in &first {
+        let mut sorted = round.clone();
+        sorted.sort();
+        assert_eq!(sorted, (0..20).collect::<Vec<INT>>());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_dice() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let mut total = 0;
+
+    for _ in 0..2000 {
+        let roll = engine.eval::<INT>("dice(2, 6)")?;
+
+        assert!((2..=12).contains(&roll), "Out of range: {}", roll);
+        total += roll;
+    }
+
+    let mean = total as f64 / 2000.0;
+    assert!((mean - 7.0).abs() < 0.25, "Mean should be near 7: {}", mean);
+
+    assert_eq!(engine.eval::<INT>("dice(0, 6)")?, 0);
+    assert_eq!(engine.eval::<INT>("dice(3, 1)")?, 3);
+
+    assert!(engine.eval::<INT>("dice(-1, 6)").is_err());
+    assert!(engine.eval::<INT>("dice(2, 0)").is_err());
+    assert!(engine
+        .eval::<INT>(&format!("dice(2, {})", INT::MAX))
+        .is_err());
+    assert!(engine
+        .eval::<INT>(&format!("dice({}, 2)", INT::MAX / 2))
+        .is_err());
+    assert!(engine
+        .eval::<INT>(&format!("dice_sum({}, 2)", INT::MAX / 2))
+        .is_err());
+
+    #[cfg(feature = "array")]
+    {
+        let rolls = engine
+            .eval::<rhai::Dynamic>("dice_roll(100, 4)")?
+            .into_typed_array::<INT>()?;
+
+        assert_eq!(rolls.len(), 100);
+        assert!(rolls.iter().all(|v| (1..=4).contains(v)));
+
+        assert!(engine.eval::<Array>("dice_roll(0, 6)")?.is_empty());
+        assert!(engine.eval::<Array>("dice_roll(-1, 6)").is_err());
+        assert!(engine.eval::<Array>("dice_roll(2, 0)").is_err());
+        assert!(engine
+            .eval::<Array>(&format!("dice_roll({}, 2)", INT::MAX / 2))
+            .is_err());
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "float")]
+#[test]
+fn test_rand_exp_bucketed() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let mut counts = [0; 4];
+
+    for _ in 0..4000 {
+        let value = engine.eval::<INT>("rand_exp_bucketed(4, 10.0)")?;
+
+        assert!((1..10000).contains(&value), "Out of range: {}", value);
+
+        counts[(value as f64).log10().floor() as usize] += 1;
+    }
+
+    // Expected frequencies: 0.9009, 0.0901, 0.0090, 0.0009
+    assert!(
+        counts.windows(2).all(|w| w[0] > w[1]),
+        "Smaller buckets should be selected more often: {:?}",
+        counts
+    );
+    assert!(
+        (counts[0] as f64 / 4000.0 - 0.9009).abs() < 0.02,
+        "First bucket frequency should be near 0.9: {:?}",
+        counts
+    );
+
+    // Larger buckets are reached
+    let max = engine.eval::<INT>(
+        "
+            let max = 0;
+            for i in 0..2000 { let v = rand_exp_bucketed(3, 2.0); if v > max { max = v; } }
+            max
+        ",
+    )?;
+    assert!(
+        (4..8).contains(&max),
+        "Largest bucket should be reached: {}",
+        max
+    );
+
+    assert_eq!(engine.eval::<INT>("rand_exp_bucketed(1, 2.0)")?, 1);
+
+    assert!(engine.eval::<INT>("rand_exp_bucketed(0, 10.0)").is_err());
+    assert!(engine.eval::<INT>("rand_exp_bucketed(3, 1.0)").is_err());
+    assert!(engine.eval::<INT>("rand_exp_bucketed(100, 10.0)").is_err());
+
+    Ok(())
+}
+
+#[cfg(feature = "float")]
+#[test]
+fn test_rand_float_range_bounds() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    // The smallest possible range above 1.0 contains only 1.0 when exclusive
+    let next = FLOAT::from_bits(1.0_f64.to_bits() + 1);
+
+    for _ in 0..200 {
+        let value = engine.eval::<FLOAT>(&format!("rand_float(1.0, {:?})", next))?;
+        assert_eq!(value, 1.0, "End should be excluded");
+
+        let value = engine.eval::<FLOAT>("rand_float(-2.0, 3.0)")?;
+        assert!((-2.0..3.0).contains(&value), "Out of range: {}", value);
+
+        let value = engine.eval::<FLOAT>("rand_float_inclusive(-2.0, 3.0)")?;
+        assert!((-2.0..=3.0).contains(&value), "Out of range: {}", value);
+    }
+
+    assert_eq!(engine.eval::<FLOAT>("rand_float_inclusive(2.5, 2.5)")?, 2.5);
+
+    // Floating-point ranges cannot be written in Rhai, so only the two-argument form exists
+    assert!(engine.eval::<FLOAT>("rand_float(0.0..1.0)").is_err());
+    assert!(engine.eval::<FLOAT>("rand_float(0..1)").is_err());
+
+    assert!(engine.eval::<FLOAT>("rand_float(2.5, 2.5)").is_err());
+    assert!(engine.eval::<FLOAT>("rand_float(3.0, 1.0)").is_err());
+    assert!(engine
+        .eval::<FLOAT>("rand_float_inclusive(3.0, 1.0)")
+        .is_err());
+
+    let max = format!("{:?}", FLOAT::MAX);
+    assert!(engine
+        .eval::<FLOAT>(&format!("rand_float(-{}, {})", max, max))
+        .is_err());
+    assert!(engine
+        .eval::<FLOAT>(&format!("rand_float_inclusive(-{}, {})", max, max))
+        .is_err());
+
+    Ok(())
+}
+
+#[cfg(feature = "float")]
+#[test]
+fn test_cooldown_roll() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let gaps = engine
+        .eval::<rhai::Dynamic>(
+            "
+                let steps = 0;
+                let gaps = [];
+                for turn in 0..2000 {
+                    if cooldown_roll(1.0, steps, 5) {
+                        gaps.push(steps);
+                        steps = 0;
+                    } else {
+                        steps += 1;
+                    }
+                }
+                gaps
+            ",
+        )?
+        .into_typed_array::<INT>()?;
+
+    // With p = 1.0, every success happens exactly when the cooldown ends
+    assert!(!gaps.is_empty());
+    assert!(
+        gaps.iter().all(|&gap| gap == 5),
+        "No success should occur within the cooldown: {:?}",
+        gaps
+    );
+
+    let hits = engine.eval::<INT>(
+        "
+            let hits = 0;
+            for i in 0..4000 { if cooldown_roll(0.3, 10, 5) { hits += 1; } }
+            hits
+        ",
+    )?;
+    let rate = hits as f64 / 4000.0;
+    assert!(
+        (rate - 0.3).abs() < 0.03,
+        "Rolls after the cooldown should succeed with probability 0.3: {}",
+        rate
+    );
+
+    assert!(!engine.eval::<bool>("cooldown_roll(1.0, 4, 5)")?);
+    assert!(engine.eval::<bool>("cooldown_roll(1.0, 0, 0)")?);
+
+    assert!(engine.eval::<bool>("cooldown_roll(1.5, 0, 0)").is_err());
+    assert!(engine.eval::<bool>("cooldown_roll(0.5, -1, 0)").is_err());
+    assert!(engine.eval::<bool>("cooldown_roll(0.5, 0, -1)").is_err());
+
+    Ok(())
+}
+
+#[cfg(all(feature = "float", feature = "array"))]
+#[test]
+fn test_rand_in_cone() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let axis = [1.0, 2.0, -2.0];
+    let axis_length = 3.0;
+
+    let mut widest: FLOAT = 0.0;
+
+    for _ in 0..1000 {
+        let vector = engine
+            .eval::<rhai::Dynamic>("rand_in_cone([1, 2, -2], 0.4)")?
+            .into_typed_array::<FLOAT>()?;
+
+        assert_eq!(vector.len(), 3);
+
+        let length = vector.iter().map(|v| v * v).sum::<FLOAT>().sqrt();
+        assert!(
+            (length - 1.0).abs() < 1e-9,
+            "Not a unit vector: {:?}",
+            vector
+        );
+
+        let cos = vector.iter().zip(axis).map(|(a, b)| a * b).sum::<FLOAT>() / axis_length;
+        let angle = cos.clamp(-1.0, 1.0).acos();
+        assert!(
+            angle <= 0.4 + 1e-9,
+            "Vector {:?} should be within the cone: {}",
+            vector,
+            angle
+        );
+
+        widest = widest.max(angle);
+    }
+
+    assert!(widest > 0.3, "Vectors should fill the cone: {}", widest);
+
+    let vector = engine
+        .eval::<rhai::Dynamic>("rand_in_cone([0, 0, 5], 0.0)")?
+        .into_typed_array::<FLOAT>()?;
+    assert!(
+        (vector[2] - 1.0).abs() < 1e-9,
+        "Should be the axis: {:?}",
+        vector
+    );
+
+    assert!(engine
+        .eval::<Array>("rand_in_cone([0, 0, 0], 0.5)")
+        .is_err());
+    assert!(engine.eval::<Array>("rand_in_cone([0, 1], 0.5)").is_err());
+    assert!(engine
+        .eval::<Array>("rand_in_cone([0, 1, 0], -0.1)")
+        .is_err());
+    assert!(engine
+        .eval::<Array>("rand_in_cone([0, 1, 0], 4.0)")
+        .is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_rand_empty_range() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    assert!(engine.eval::<INT>("rand(5..5)").is_err());
+    assert!(engine.eval::<INT>("rand(6..5)").is_err());
+    assert!(engine.eval::<INT>("rand(6..=5)").is_err());
+
+    assert_eq!(engine.eval::<INT>("rand(5..6)")?, 5);
+    assert_eq!(engine.eval::<INT>("rand(5..=5)")?, 5);
+
+    for _ in 0..100 {
+        let value = engine.eval::<INT>("rand(-3..3)")?;
+        assert!((-3..3).contains(&value), "Out of range: {}", value);
+
+        let value = engine.eval::<INT>("rand(-3..=3)")?;
+        assert!((-3..=3).contains(&value), "Out of range: {}", value);
+    }
+
+    Ok(())
+}
+
+#[cfg(all(feature = "float", feature = "array"))]
+#[test]
+fn test_softmax_sample() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let counts = |temperature: FLOAT| -> Result<Vec<INT>, Box<EvalAltResult>> {
+        Ok(engine
+            .eval::<rhai::Dynamic>(&format!(
+                "
+                    let counts = [0, 0, 0];
+                    for i in 0..3000 {{ counts[softmax_sample([1.0, 3.0, 2.0], {:?})] += 1; }}
+                    counts
+                ",
+                temperature
+            ))?
+            .into_typed_array::<INT>()?)
+    };
+
+    let warm = counts(1.0)?;
+    let cold = counts(0.25)?;
+
+    assert!(
+        warm[1] > warm[2] && warm[2] > warm[0],
+        "Higher logits should be more frequent: {:?}",
+        warm
+    );
+    assert!(
+        cold[1] > warm[1] + 500,
+        "Lower temperatures should sharpen the distribution: {:?} vs {:?}",
+        cold,
+        warm
+    );
+
+    // Huge logits do not overflow
+    assert_eq!(
+        engine.eval::<INT>("softmax_sample([1000.0, 10000.0], 1.0)")?,
+        1
+    );
+
+    assert!(engine.eval::<INT>("softmax_sample([], 1.0)").is_err());
+    assert!(engine.eval::<INT>("softmax_sample([1.0], 0.0)").is_err());
+    assert!(engine
+        .eval::<INT>("softmax_sample([1.0, 'x'], 1.0)")
+        .is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_rand_extreme_ranges() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    engine.register_fn("int_min", || INT::MIN);
+    engine.register_fn("int_max", || INT::MAX);
+
+    let mut negative = false;
+    let mut positive = false;
+
+    for _ in 0..1000 {
+        let value = engine.eval::<INT>("rand(int_min(), int_max())")?;
+        negative |= value < 0;
+        positive |= value > 0;
+
+        let value = engine.eval::<INT>("rand(int_min()..=int_max())")?;
+        negative |= value < 0;
+        positive |= value > 0;
+
+        let value = engine.eval::<INT>("rand(0, int_max())")?;
+        assert!(value >= 0, "Out of range: {}", value);
+
+        let value = engine.eval::<INT>("rand(0..=int_max())")?;
+        assert!(value >= 0, "Out of range: {}", value);
+
+        let value = engine.eval::<INT>("rand(int_min(), 0)")?;
+        assert!(value <= 0, "Out of range: {}", value);
+
+        let value = engine.eval::<INT>("rand(int_min()..=0)")?;
+        assert!(value <= 0, "Out of range: {}", value);
+
+        let value = engine.eval::<INT>("rand(int_max() - 1, int_max())")?;
+        assert!(value >= INT::MAX - 1, "Out of range: {}", value);
+    }
+
+    assert!(
+        negative && positive,
+        "Full-width ranges should cover both signs"
+    );
+
+    assert_eq!(engine.eval::<INT>("rand(int_max()..=int_max())")?, INT::MAX);
+    assert_eq!(engine.eval::<INT>("rand(int_min()..=int_min())")?, INT::MIN);
+    assert_eq!(engine.eval::<INT>("rand(int_max(), int_max())")?, INT::MAX);
+    assert_eq!(engine.eval::<INT>("rand(int_min(), int_min())")?, INT::MIN);
+    assert_eq!(engine.eval::<INT>("rand(5, 5)")?, 5);
+    assert!(engine.eval::<INT>("rand(int_max(), int_min())").is_err());
+
+    Ok(())
+}
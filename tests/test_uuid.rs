@@ -0,0 +1,55 @@
+#![cfg(feature = "uuid")]
+
+use rhai::{packages::Package, Engine, EvalAltResult};
+use rhai_rand::RandomPackage;
+
+#[test]
+fn test_rand_uuid_v7() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    let mut previous = String::new();
+
+    for _ in 0..1000 {
+        let uuid = engine.eval::<String>("rand_uuid_v7()")?;
+
+        let groups: Vec<&str> = uuid.split('-').collect();
+        assert_eq!(
+            groups.iter().map(|g| g.len()).collect::<Vec<_>>(),
+            vec![8, 4, 4, 4, 12],
+            "Invalid UUID format: {}",
+            uuid
+        );
+        assert!(uuid
+            .chars()
+            .all(|c| c == '-' || c.is_ascii_digit() || ('a'..='f').contains(&c)));
+
+        assert_eq!(&groups[2][..1], "7", "Version should be 7: {}", uuid);
+        assert!(
+            "89ab".contains(&groups[3][..1]),
+            "Variant should be RFC 9562: {}",
+            uuid
+        );
+
+        assert!(
+            uuid > previous,
+            "UUIDs should be ordered: {} <= {}",
+            uuid,
+            previous
+        );
+        previous = uuid;
+    }
+
+    // The timestamp prefix is the current time in milliseconds
+    let uuid = engine.eval::<String>("rand_uuid_v7()")?;
+    let timestamp = u64::from_str_radix(&uuid.replace('-', "")[..12], 16).unwrap();
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64;
+
+    assert!(now.abs_diff(timestamp) < 10_000, "{} vs {}", timestamp, now);
+
+    Ok(())
+}